@@ -0,0 +1,17 @@
+//! Feeds arbitrary 40-bit frames to the decoders of all the models.
+#![no_main]
+
+use dht_pio::mock::{MockDelay, MockDht, MockResponse};
+use dht_pio::{Dht11, Dht22, Dht22Type2};
+use libfuzzer_sys::fuzz_target;
+
+fn answering(frame: [u8; 5]) -> MockDht<core::iter::Once<MockResponse>> {
+    MockDht::new(core::iter::once(MockResponse::Frame(frame)))
+}
+
+fuzz_target!(|frame: [u8; 5]| {
+    let mut delay = MockDelay::default();
+    let _ = Dht22::from_transport(answering(frame)).read(&mut delay);
+    let _ = Dht22Type2::from_transport(answering(frame)).read(&mut delay);
+    let _ = Dht11::from_transport(answering(frame)).read(&mut delay);
+});
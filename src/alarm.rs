@@ -0,0 +1,253 @@
+//! Threshold alarms on the stream of readings.
+//!
+//! ```ignore
+//! let mut alarms = Alarms::new()
+//!     .over_temperature(Threshold::new(30.0).hysteresis(1.0).hold(Duration::secs(60)))
+//!     .offline_after(Duration::secs(30));
+//!
+//! alarms.update(dht.read(&mut delay).ok().as_ref(), timer.get_counter(), |event| match event {
+//!     AlarmEvent::Raised(kind) => { /* ... */ }
+//!     AlarmEvent::Cleared(kind) => { /* ... */ }
+//! });
+//! ```
+
+use crate::filter::Value;
+use crate::{DhtResult, Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AlarmKind {
+    OverTemperature,
+    UnderTemperature,
+    OverHumidity,
+    UnderHumidity,
+    /// The temperature is close to the dew point.
+    DewPoint,
+    /// No reading for too long.
+    Offline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AlarmEvent {
+    Raised(AlarmKind),
+    Cleared(AlarmKind),
+}
+
+/// Limit of an alarm.
+///
+/// The alarm is raised once the limit is crossed, and cleared once the value comes back by more
+/// than the hysteresis. Both changes must last for the hold time.
+#[derive(Debug, Clone, Copy)]
+pub struct Threshold {
+    limit: f32,
+    hysteresis: f32,
+    hold: Duration,
+
+    active: bool,
+    /// Start of the change of state waiting for the hold time.
+    pending_since: Option<Instant>,
+}
+
+impl Threshold {
+    /// No hysteresis and no hold time.
+    pub fn new(limit: f32) -> Self {
+        Self {
+            limit,
+            hysteresis: 0.0,
+            hold: Duration::from_ticks(0),
+            active: false,
+            pending_since: None,
+        }
+    }
+
+    #[must_use]
+    pub fn hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    #[must_use]
+    pub fn hold(mut self, hold: Duration) -> Self {
+        self.hold = hold;
+        self
+    }
+
+    /// Returns the new state of the alarm if it changed.
+    fn update(&mut self, value: f32, above: bool, now: Instant) -> Option<bool> {
+        let (crossed, back) = if above {
+            (value > self.limit, value < self.limit - self.hysteresis)
+        } else {
+            (value < self.limit, value > self.limit + self.hysteresis)
+        };
+
+        let wanted = if self.active { !back } else { crossed };
+        if wanted == self.active {
+            self.pending_since = None;
+            return None;
+        }
+
+        let since = *self.pending_since.get_or_insert(now);
+        if now
+            .checked_duration_since(since)
+            .is_some_and(|held| held >= self.hold)
+        {
+            self.active = wanted;
+            self.pending_since = None;
+            Some(wanted)
+        } else {
+            None
+        }
+    }
+}
+
+/// Set of alarms watching the readings of a sensor.
+#[derive(Debug, Clone, Default)]
+pub struct Alarms {
+    over_temperature: Option<Threshold>,
+    under_temperature: Option<Threshold>,
+    over_humidity: Option<Threshold>,
+    under_humidity: Option<Threshold>,
+    dew_point: Option<Threshold>,
+    offline_after: Option<Duration>,
+
+    last_seen: Option<Instant>,
+    offline: bool,
+}
+
+impl Alarms {
+    /// No alarm is enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Temperature (°C) above the limit.
+    #[must_use]
+    pub fn over_temperature(mut self, threshold: Threshold) -> Self {
+        self.over_temperature = Some(threshold);
+        self
+    }
+
+    /// Temperature (°C) below the limit.
+    #[must_use]
+    pub fn under_temperature(mut self, threshold: Threshold) -> Self {
+        self.under_temperature = Some(threshold);
+        self
+    }
+
+    /// Humidity (%RH) above the limit.
+    #[must_use]
+    pub fn over_humidity(mut self, threshold: Threshold) -> Self {
+        self.over_humidity = Some(threshold);
+        self
+    }
+
+    /// Humidity (%RH) below the limit.
+    #[must_use]
+    pub fn under_humidity(mut self, threshold: Threshold) -> Self {
+        self.under_humidity = Some(threshold);
+        self
+    }
+
+    /// Difference between the temperature and the dew point (°C) below the limit.
+    #[must_use]
+    pub fn dew_point(mut self, threshold: Threshold) -> Self {
+        self.dew_point = Some(threshold);
+        self
+    }
+
+    /// No reading for `timeout`.
+    #[must_use]
+    pub fn offline_after(mut self, timeout: Duration) -> Self {
+        self.offline_after = Some(timeout);
+        self
+    }
+
+    /// Returns `true` if the alarm is raised.
+    pub fn is_active(&self, kind: AlarmKind) -> bool {
+        let threshold = match kind {
+            AlarmKind::OverTemperature => self.over_temperature,
+            AlarmKind::UnderTemperature => self.under_temperature,
+            AlarmKind::OverHumidity => self.over_humidity,
+            AlarmKind::UnderHumidity => self.under_humidity,
+            AlarmKind::DewPoint => self.dew_point,
+            AlarmKind::Offline => return self.offline,
+        };
+
+        threshold.is_some_and(|t| t.active)
+    }
+
+    /// Feeds the reading made at `now` (`None` if the read failed, or to only check the offline
+    /// alarm), and calls `on_event` for each alarm raised or cleared.
+    pub fn update<T: Value, H: Value>(
+        &mut self,
+        reading: Option<&DhtResult<T, H>>,
+        now: Instant,
+        mut on_event: impl FnMut(AlarmEvent),
+    ) {
+        let last_seen = *self.last_seen.get_or_insert(now);
+
+        let Some(reading) = reading else {
+            let timed_out = self.offline_after.is_some_and(|timeout| {
+                now.checked_duration_since(last_seen)
+                    .is_some_and(|elapsed| elapsed >= timeout)
+            });
+
+            if timed_out && !self.offline {
+                self.offline = true;
+                on_event(AlarmEvent::Raised(AlarmKind::Offline));
+            }
+            return;
+        };
+
+        self.last_seen = Some(now);
+        if self.offline {
+            self.offline = false;
+            on_event(AlarmEvent::Cleared(AlarmKind::Offline));
+        }
+
+        let temperature = reading.temperature.to_f32();
+        let humidity = reading.humidity.to_f32();
+        let spread = temperature - reading.dew_point();
+
+        let checks = [
+            (
+                &mut self.over_temperature,
+                AlarmKind::OverTemperature,
+                temperature,
+                true,
+            ),
+            (
+                &mut self.under_temperature,
+                AlarmKind::UnderTemperature,
+                temperature,
+                false,
+            ),
+            (
+                &mut self.over_humidity,
+                AlarmKind::OverHumidity,
+                humidity,
+                true,
+            ),
+            (
+                &mut self.under_humidity,
+                AlarmKind::UnderHumidity,
+                humidity,
+                false,
+            ),
+            (&mut self.dew_point, AlarmKind::DewPoint, spread, false),
+        ];
+
+        for (threshold, kind, value, above) in checks {
+            let Some(threshold) = threshold else {
+                continue;
+            };
+
+            match threshold.update(value, above, now) {
+                Some(true) => on_event(AlarmEvent::Raised(kind)),
+                Some(false) => on_event(AlarmEvent::Cleared(kind)),
+                None => (),
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{InputPin, OutputPin};
+
+use crate::stats::TimeoutPhase;
+use crate::{DhtError, DhtTransport, Transport};
+
+/// Longest pulse of the sensor, in polls of the line. A poll waits 1µs on top of the pin read, so the
+/// limit is at least 100µs (the longest pulse of the protocol is ~80µs), and longer on a slow CPU.
+const MAX_PULSE_POLLS: u32 = 100;
+
+/// DHT protocol implemented with a single open-drain pin.
+///
+/// The pin must be configured as open-drain (or be an input/output pin with an external pull-up),
+/// the line is released by setting the pin high. Since the bit length is measured by polling the pin,
+/// the read should not be interrupted (for example by running it in a critical section).
+pub struct DhtBitBang<P: InputPin + OutputPin> {
+    pin: P,
+    timeout_phase: TimeoutPhase,
+}
+
+impl<P: InputPin + OutputPin> DhtBitBang<P> {
+    pub fn new(mut pin: P) -> Self {
+        pin.set_high().ok();
+        Self {
+            pin,
+            timeout_phase: TimeoutPhase::Response,
+        }
+    }
+
+    /// Returns the pin.
+    pub fn free(self) -> P {
+        self.pin
+    }
+
+    /// Waits while the line is at `level`, and returns the number of polls (the length of the pulse,
+    /// in units which only make sense compared with each other).
+    fn wait_while<D: DelayNs>(&mut self, level: bool, delay: &mut D) -> Result<u32, DhtError> {
+        let mut polls = 0;
+
+        while self.pin.is_high().map_err(|_| DhtError::PinError)? == level {
+            if polls > MAX_PULSE_POLLS {
+                self.pin.set_high().ok();
+                return Err(DhtError::Timeout);
+            }
+
+            delay.delay_us(1);
+            polls += 1;
+        }
+
+        Ok(polls)
+    }
+}
+
+impl<P: InputPin + OutputPin> DhtTransport for DhtBitBang<P> {
+    fn transfer<D: DelayNs>(
+        &mut self,
+        start_signal_length: u32,
+        delay: &mut D,
+    ) -> Result<[u8; 5], DhtError> {
+        // The line is released (pulled up) while idle
+        if self.pin.is_low().map_err(|_| DhtError::PinError)? {
+            return Err(DhtError::LineStuckLow);
+        }
+
+        self.pin.set_low().map_err(|_| DhtError::PinError)?;
+        delay.delay_ms(start_signal_length);
+        if self.pin.is_high().map_err(|_| DhtError::PinError)? {
+            self.pin.set_high().ok();
+            return Err(DhtError::LineStuckHigh);
+        }
+        self.pin.set_high().map_err(|_| DhtError::PinError)?;
+        delay.delay_us(20);
+
+        // Sensor response: ~80µs low, then ~80µs high
+        self.timeout_phase = TimeoutPhase::Response;
+        self.wait_while(true, delay)?;
+        self.wait_while(false, delay)?;
+        self.wait_while(true, delay)?;
+
+        self.timeout_phase = TimeoutPhase::Data;
+        let mut frame = [0u8; 5];
+
+        for byte in &mut frame {
+            for _ in 0..8 {
+                // Each bit starts with ~50µs low, followed by ~27µs (0) or ~70µs (1) high
+                let low = self.wait_while(false, delay)?;
+                let high = self.wait_while(true, delay)?;
+                *byte = (*byte << 1) | u8::from(high > low);
+            }
+        }
+
+        Ok(frame)
+    }
+}
+
+impl<P: InputPin + OutputPin> Transport for DhtBitBang<P> {
+    fn timeout_phase(&self) -> TimeoutPhase {
+        self.timeout_phase
+    }
+
+    fn set_sensor_power(&mut self, powered: bool) {
+        // Released (pulled up) while the sensor is powered, driven low otherwise
+        if powered {
+            self.pin.set_high().ok();
+        } else {
+            self.pin.set_low().ok();
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Calibration of the readings, applied by the sensors (see [`Sensor::calibration`](crate::Sensor::calibration)).
+
+use crate::filter::Value;
+use crate::DhtResult;
+
+/// Version of the byte representation of a [`Calibration`].
+const VERSION: u8 = 1;
+
+/// Linear correction of a value: `corrected = raw * gain + offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Correction {
+    pub gain: f32,
+    pub offset: f32,
+}
+
+impl Correction {
+    pub const IDENTITY: Self = Self {
+        gain: 1.0,
+        offset: 0.0,
+    };
+
+    pub fn offset(offset: f32) -> Self {
+        Self { gain: 1.0, offset }
+    }
+
+    pub fn linear(gain: f32, offset: f32) -> Self {
+        Self { gain, offset }
+    }
+
+    /// Correction going through two reference points: `(raw, reference)` values measured at two
+    /// different levels. Returns `None` if the two raw values are equal.
+    pub fn two_point(low: (f32, f32), high: (f32, f32)) -> Option<Self> {
+        let (raw_low, ref_low) = low;
+        let (raw_high, ref_high) = high;
+
+        if raw_high == raw_low {
+            return None;
+        }
+
+        let gain = (ref_high - ref_low) / (raw_high - raw_low);
+        Some(Self {
+            gain,
+            offset: ref_low - raw_low * gain,
+        })
+    }
+
+    pub fn apply(&self, raw: f32) -> f32 {
+        raw * self.gain + self.offset
+    }
+}
+
+impl Default for Correction {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Corrections of the temperature and of the humidity of a sensor.
+///
+/// The calibrated humidity is kept between 0% and 100%, the readings are left untouched by the default (identity) calibration.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Calibration {
+    pub temperature: Correction,
+    pub humidity: Correction,
+}
+
+impl Calibration {
+    /// Size of the byte representation: version, 4 `f32` (little endian) and checksum.
+    pub const SIZE: usize = 18;
+
+    pub fn new(temperature: Correction, humidity: Correction) -> Self {
+        Self {
+            temperature,
+            humidity,
+        }
+    }
+
+    pub fn apply<T: Value, H: Value>(&self, reading: DhtResult<T, H>) -> DhtResult<T, H> {
+        if *self == Self::default() {
+            return reading;
+        }
+
+        let humidity = self.humidity.apply(reading.humidity.to_f32());
+
+        DhtResult {
+            temperature: T::from_f32(self.temperature.apply(reading.temperature.to_f32())),
+            humidity: H::from_f32(humidity.clamp(0.0, 100.0)),
+        }
+    }
+
+    /// Compact representation, to store the calibration in flash.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0] = VERSION;
+
+        let values = [
+            self.temperature.gain,
+            self.temperature.offset,
+            self.humidity.gain,
+            self.humidity.offset,
+        ];
+        for (chunk, value) in bytes[1..17].chunks_exact_mut(4).zip(values) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+
+        bytes[17] = checksum(&bytes[..17]);
+        bytes
+    }
+
+    /// Reads the representation made by [`Self::to_bytes`], returns `None` if it is not valid
+    /// (e.g. erased flash).
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; Self::SIZE] = bytes.try_into().ok()?;
+
+        if bytes[0] != VERSION || checksum(&bytes[..17]) != bytes[17] {
+            return None;
+        }
+
+        let value = |i: usize| {
+            let start = 1 + 4 * i;
+            f32::from_le_bytes([
+                bytes[start],
+                bytes[start + 1],
+                bytes[start + 2],
+                bytes[start + 3],
+            ])
+        };
+
+        Some(Self {
+            temperature: Correction::linear(value(0), value(1)),
+            humidity: Correction::linear(value(2), value(3)),
+        })
+    }
+}
+
+/// Same checksum as the frames of the sensor: sum of the bytes.
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |sum, b| sum.wrapping_add(*b))
+}
@@ -0,0 +1,193 @@
+//! Comfort classification of the readings: simplified ASHRAE 55 comfort zone, humidex bands and
+//! mould risk.
+//!
+//! The enums have fixed `u8` codes, to be mapped to labels by the application.
+
+use crate::filter::Value;
+use crate::{DhtResult, Duration, Instant};
+
+/// Simplified ASHRAE 55 comfort zone: 20°C to 26°C, 30%RH to 60%RH and a dew point up to 16.8°C
+/// (humidity ratio of 0.012).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Zone {
+    Comfortable = 0,
+    Cold = 1,
+    Hot = 2,
+    Dry = 3,
+    Humid = 4,
+}
+
+impl Zone {
+    pub fn classify<T: Value, H: Value>(reading: &DhtResult<T, H>) -> Self {
+        let temperature = reading.temperature.to_f32();
+
+        if temperature < 20.0 {
+            Self::Cold
+        } else if temperature > 26.0 {
+            Self::Hot
+        } else {
+            match Label::classify(reading) {
+                Label::Dry => Self::Dry,
+                Label::Humid => Self::Humid,
+                _ => Self::Comfortable,
+            }
+        }
+    }
+}
+
+/// Humidex comfort bands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum HumidexBand {
+    /// Below 30.
+    Comfortable = 0,
+    /// 30 to 39.
+    SomeDiscomfort = 1,
+    /// 40 to 45.
+    GreatDiscomfort = 2,
+    /// 46 to 53.
+    Dangerous = 3,
+    /// 54 and above.
+    HeatStroke = 4,
+}
+
+impl HumidexBand {
+    pub fn classify<T: Value, H: Value>(reading: &DhtResult<T, H>) -> Self {
+        match humidex(reading) {
+            h if h < 30.0 => Self::Comfortable,
+            h if h < 40.0 => Self::SomeDiscomfort,
+            h if h < 46.0 => Self::GreatDiscomfort,
+            h if h < 54.0 => Self::Dangerous,
+            _ => Self::HeatStroke,
+        }
+    }
+}
+
+/// Humidex (felt temperature, in °C) of the reading.
+pub fn humidex<T: Value, H: Value>(reading: &DhtResult<T, H>) -> f32 {
+    let dew_point = reading.dew_point();
+    // Vapour pressure (hPa)
+    let e = 6.11 * libm::expf(5417.753 * (1.0 / 273.16 - 1.0 / (273.15 + dew_point)));
+
+    reading.temperature.to_f32() + 5.0 / 9.0 * (e - 10.0)
+}
+
+/// Humidity label of the readings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Label {
+    Comfortable = 0,
+    Dry = 1,
+    Humid = 2,
+    /// Humidity above the mould threshold for too long.
+    MouldRisk = 3,
+}
+
+impl Label {
+    /// Label of the humidity of the reading, never [`Label::MouldRisk`] (see [`Comfort`]).
+    pub fn classify<T: Value, H: Value>(reading: &DhtResult<T, H>) -> Self {
+        let humidity = reading.humidity.to_f32();
+
+        if humidity < 30.0 {
+            Self::Dry
+        } else if humidity > 60.0 || reading.dew_point() > 16.8 {
+            Self::Humid
+        } else {
+            Self::Comfortable
+        }
+    }
+}
+
+/// Classification of a reading.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Classification {
+    pub zone: Zone,
+    pub humidex: f32,
+    pub humidex_band: HumidexBand,
+    pub label: Label,
+}
+
+/// Comfort classifier, keeping track of the time spent above the mould threshold.
+///
+/// ```ignore
+/// let mut comfort = Comfort::new();
+/// // ...
+/// let classification = comfort.update(&dht.read(&mut delay)?, timer.get_counter());
+/// display.show(classification.label as u8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Comfort {
+    mould_humidity: f32,
+    mould_after: Duration,
+
+    humid_since: Option<Instant>,
+}
+
+impl Default for Comfort {
+    fn default() -> Self {
+        Self {
+            mould_humidity: 70.0,
+            mould_after: Duration::hours(12),
+            humid_since: None,
+        }
+    }
+}
+
+impl Comfort {
+    /// Mould risk after 12 hours above 70%RH.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mould risk after `duration` above `humidity` (%RH).
+    #[must_use]
+    pub fn mould_risk(mut self, humidity: f32, duration: Duration) -> Self {
+        self.mould_humidity = humidity;
+        self.mould_after = duration;
+        self
+    }
+
+    /// Returns `true` if the humidity has been above the mould threshold for too long at `now`.
+    pub fn is_mould_risk(&self, now: Instant) -> bool {
+        self.humid_since.is_some_and(|since| {
+            now.checked_duration_since(since)
+                .is_some_and(|elapsed| elapsed >= self.mould_after)
+        })
+    }
+
+    /// Classifies the reading made at `now`.
+    pub fn update<T: Value, H: Value>(
+        &mut self,
+        reading: &DhtResult<T, H>,
+        now: Instant,
+    ) -> Classification {
+        if reading.humidity.to_f32() > self.mould_humidity {
+            self.humid_since.get_or_insert(now);
+        } else {
+            self.humid_since = None;
+        }
+
+        let label = if self.is_mould_risk(now) {
+            Label::MouldRisk
+        } else {
+            Label::classify(reading)
+        };
+
+        Classification {
+            zone: Zone::classify(reading),
+            humidex: humidex(reading),
+            humidex_band: HumidexBand::classify(reading),
+            label,
+        }
+    }
+
+    /// Forgets the time spent above the mould threshold.
+    pub fn reset(&mut self) {
+        self.humid_since = None;
+    }
+}
@@ -0,0 +1,211 @@
+//! Condensation and frost risk on a surface (window, pipe, cold plate), from the reading of the
+//! air and the temperature of the surface measured by another sensor.
+//!
+//! The risk levels rise when the margin crosses a threshold, and fall back only once the margin
+//! is above the threshold plus the hysteresis. A new level must be confirmed by consecutive
+//! updates, so a single bad frame does not change it.
+
+use crate::filter::Value;
+use crate::DhtResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Risk {
+    None,
+    Low,
+    High,
+}
+
+/// Result of an update of the [`Detector`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Assessment {
+    /// Surface temperature minus the dew point (°C), condensation forms below 0.
+    pub condensation_margin: f32,
+    /// Surface temperature minus the frost point (°C), frost forms below 0 on a surface below 0°C.
+    pub frost_margin: f32,
+    pub condensation: Risk,
+    pub frost: Risk,
+}
+
+/// Margins (°C) under which the risk is [`Risk::Low`] and [`Risk::High`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Margins {
+    pub low: f32,
+    pub high: f32,
+}
+
+/// Risk level with hysteresis and confirmation.
+#[derive(Debug, Clone, Copy)]
+struct Level {
+    current: Risk,
+    candidate: Risk,
+    count: u8,
+}
+
+impl Level {
+    const fn new() -> Self {
+        Self {
+            current: Risk::None,
+            candidate: Risk::None,
+            count: 0,
+        }
+    }
+
+    fn update(&mut self, margin: f32, margins: Margins, hysteresis: f32, confirm: u8) -> Risk {
+        // The current level is kept until the margin is above its threshold plus the hysteresis
+        let threshold = |risk: Risk, margin: f32| {
+            if self.current >= risk {
+                margin + hysteresis
+            } else {
+                margin
+            }
+        };
+
+        let raw = if margin < threshold(Risk::High, margins.high) {
+            Risk::High
+        } else if margin < threshold(Risk::Low, margins.low) {
+            Risk::Low
+        } else {
+            Risk::None
+        };
+
+        if raw == self.current {
+            self.count = 0;
+        } else {
+            if raw != self.candidate {
+                self.candidate = raw;
+                self.count = 0;
+            }
+            self.count += 1;
+
+            if self.count >= confirm {
+                self.current = raw;
+                self.count = 0;
+            }
+        }
+
+        self.current
+    }
+}
+
+/// Condensation and frost risk detector of a surface.
+///
+/// ```ignore
+/// let mut detector = Detector::new();
+/// // ...
+/// let assessment = detector.update(&dht.read(&mut delay)?, window_temperature);
+/// if assessment.condensation == Risk::High {
+///     // turn the ventilation on
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Detector {
+    condensation_margins: Margins,
+    frost_margins: Margins,
+    hysteresis: f32,
+    confirm: u8,
+
+    condensation: Level,
+    frost: Level,
+}
+
+impl Default for Detector {
+    fn default() -> Self {
+        Self {
+            condensation_margins: Margins {
+                low: 3.0,
+                high: 1.0,
+            },
+            frost_margins: Margins {
+                low: 3.0,
+                high: 1.0,
+            },
+            hysteresis: 0.5,
+            confirm: 2,
+            condensation: Level::new(),
+            frost: Level::new(),
+        }
+    }
+}
+
+impl Detector {
+    /// Low risk under a margin of 3°C, high risk under 1°C, with a hysteresis of 0.5°C and
+    /// levels confirmed by 2 consecutive updates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn condensation_margins(mut self, margins: Margins) -> Self {
+        self.condensation_margins = margins;
+        self
+    }
+
+    #[must_use]
+    pub fn frost_margins(mut self, margins: Margins) -> Self {
+        self.frost_margins = margins;
+        self
+    }
+
+    /// Hysteresis (°C) of the margins.
+    #[must_use]
+    pub fn hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Number of consecutive updates needed to change a level (at least 1).
+    #[must_use]
+    pub fn confirm(mut self, updates: u8) -> Self {
+        self.confirm = updates.max(1);
+        self
+    }
+
+    pub fn condensation(&self) -> Risk {
+        self.condensation.current
+    }
+
+    pub fn frost(&self) -> Risk {
+        self.frost.current
+    }
+
+    /// Updates the risk levels with a reading of the air and the temperature (°C) of the surface.
+    pub fn update<T: Value, H: Value>(
+        &mut self,
+        reading: &DhtResult<T, H>,
+        surface: f32,
+    ) -> Assessment {
+        let condensation_margin = surface - reading.dew_point();
+        let frost_margin = surface - reading.frost_point();
+
+        let condensation = self.condensation.update(
+            condensation_margin,
+            self.condensation_margins,
+            self.hysteresis,
+            self.confirm,
+        );
+
+        // No frost on a surface above 0°C: its margin is at least the surface temperature
+        let frost = self.frost.update(
+            frost_margin.max(surface),
+            self.frost_margins,
+            self.hysteresis,
+            self.confirm,
+        );
+
+        Assessment {
+            condensation_margin,
+            frost_margin,
+            condensation,
+            frost,
+        }
+    }
+
+    /// Forgets the levels.
+    pub fn reset(&mut self) {
+        self.condensation = Level::new();
+        self.frost = Level::new();
+    }
+}
@@ -0,0 +1,52 @@
+//! Deadline of the conversions started by [`NonBlockingTransport::start_conversion`](crate::NonBlockingTransport::start_conversion).
+
+use crate::{DhtError, Duration, Instant};
+
+/// State of a conversion.
+#[derive(Clone, Copy)]
+pub(crate) enum Conversion {
+    /// Waiting for the frame until the deadline.
+    Running(Instant),
+    /// Deadline exceeded without frame, the conversion fails at this instant (e.g. once the line
+    /// driven low has settled).
+    Expired(Instant),
+}
+
+/// Transport receiving the frame of a [`Conversion`] in the background.
+pub(crate) trait Receiver {
+    fn conversion(&mut self) -> &mut Option<Conversion>;
+
+    /// Returns the frame once it is received.
+    fn frame(&mut self, now: Instant) -> nb::Result<[u8; 5], DhtError>;
+
+    /// Called once the deadline is exceeded without frame, returns the time to wait before [`Self::fail`].
+    fn expire(&mut self) -> Duration;
+
+    /// Ends the expired conversion and returns its error.
+    fn fail(&mut self) -> DhtError;
+
+    /// Polls the conversion in progress. A frame already received is returned even past the
+    /// deadline, which only expires a conversion still waiting for it.
+    fn poll_conversion(&mut self, now: Instant) -> nb::Result<[u8; 5], DhtError> {
+        match *self.conversion() {
+            None => Err(nb::Error::WouldBlock),
+            Some(Conversion::Running(deadline)) => match self.frame(now) {
+                Err(nb::Error::WouldBlock) if now >= deadline => {
+                    let at = now + self.expire();
+                    *self.conversion() = Some(Conversion::Expired(at));
+                    self.poll_conversion(now)
+                }
+                Err(nb::Error::WouldBlock) => Err(nb::Error::WouldBlock),
+                result => {
+                    *self.conversion() = None;
+                    result
+                }
+            },
+            Some(Conversion::Expired(at)) if now >= at => {
+                *self.conversion() = None;
+                Err(nb::Error::Other(self.fail()))
+            }
+            Some(Conversion::Expired(_)) => Err(nb::Error::WouldBlock),
+        }
+    }
+}
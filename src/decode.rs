@@ -0,0 +1,72 @@
+use crate::{Dht11Result, Dht22Result, DhtError, DhtResult};
+
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn check_frame(frame: [u8; 5]) -> Result<(u16, u16), DhtError> {
+    let data = u32::from_be_bytes([frame[0], frame[1], frame[2], frame[3]]);
+    let crc = u32::from(frame[4]);
+
+    if compute_crc(data) != crc {
+        return Err(DhtError::CrcMismatch(data, crc));
+    }
+
+    Ok((
+        (data & 0x0000FFFF) as u16,
+        ((data & 0xFFFF0000) >> 16) as u16,
+    ))
+}
+
+pub(crate) fn compute_crc(data: u32) -> u32 {
+    let mut crc: u32 = 0;
+    crc += data & 0x000000FF;
+    crc += (data & 0x0000FF00) >> 8;
+    crc += (data & 0x00FF0000) >> 16;
+    crc += (data & 0xFF000000) >> 24;
+
+    crc % 256
+}
+
+pub(crate) fn dht22(raw_temp: u16, raw_hum: u16) -> Dht22Result {
+    let mut final_t = f32::from(raw_temp & 0x7FFF);
+
+    if (raw_temp & 0x8000) > 0 {
+        final_t *= -1.0;
+    }
+
+    DhtResult {
+        temperature: final_t / 10.0,
+        humidity: f32::from(raw_hum) / 10.0,
+    }
+}
+
+#[allow(clippy::cast_possible_wrap)]
+pub(crate) fn dht22_type2(raw_temp: u16, raw_hum: u16) -> Dht22Result {
+    let tmp = raw_temp as i16;
+
+    DhtResult {
+        temperature: f32::from(tmp) / 10.0,
+        humidity: f32::from(raw_hum) / 10.0,
+    }
+}
+
+pub(crate) fn dht11(t: u16, h: u16) -> Dht11Result {
+    let mut final_t = (t & 0x7FFF) >> 8;
+
+    if (t & 0x8000) > 0 {
+        final_t = 0xFF - final_t;
+    }
+
+    DhtResult {
+        temperature: final_t,
+        humidity: h >> 8,
+    }
+}
+
+/// Measurement range of the DHT22 (both types).
+pub(crate) fn dht22_in_range(reading: &Dht22Result) -> bool {
+    (-40.0..=80.0).contains(&reading.temperature) && (0.0..=100.0).contains(&reading.humidity)
+}
+
+/// Measurement range of the DHT11, negative temperatures are decoded as `0xFF - |t|`.
+pub(crate) fn dht11_in_range(reading: &Dht11Result) -> bool {
+    (reading.temperature <= 60 || reading.temperature >= 0xFF - 20) && reading.humidity <= 100
+}
@@ -1,29 +1,115 @@
 macro_rules! define_dht {
     ($hal:path) => {
         use embedded_hal::delay::DelayNs;
+        use embedded_hal::digital::InputPin;
+        #[cfg(feature = "async")]
+        use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
 
         use pio_proc::pio_file;
 
         pub(crate) use $hal as hal;
 
-        use hal::gpio::AnyPin;
+        use hal::gpio::{
+            AnyPin, DynPullType, OutputDriveStrength, OutputSlewRate, Pin, SpecificPin,
+        };
         use hal::pio::{PIOExt, Running, StateMachine, StateMachineIndex, Tx};
         use hal::pio::{Rx, ShiftDirection, UninitStateMachine};
         use hal::Clock;
 
-        use crate::DhtError;
+        use crate::conversion::{Conversion, Receiver};
+        use crate::model::Model;
+        use crate::sensor::ANSWER_TIMEOUT;
+        use crate::stats::TimeoutPhase;
+        #[cfg(feature = "async")]
+        use crate::AsyncDhtTransport;
+        use crate::{DhtError, DhtTransport, NonBlockingTransport, Sensor, Transport};
+        use crate::{Duration, Instant};
 
-        pub(crate) struct DhtPio<const START_SIGNAL_LENGTH: u32, P: PIOExt, STI: StateMachineIndex> {
+        /// Time (in µs) for the line to reach the level driven by the state machine.
+        const LINE_SETTLE_TIME: u32 = 10;
+        /// Length (in ms) of the low pulse of the recovery.
+        const RECOVERY_LOW_TIME: u32 = 20;
+        /// Time (in ms) left to the sensor to answer the recovery pulse.
+        const RECOVERY_ANSWER_TIME: u32 = 10;
+
+        /// Pad settings of the data pin.
+        ///
+        /// The default enables the internal pull-up (enough for short cables, longer ones need an external
+        /// 4.7kΩ to 10kΩ pull-up) and keeps the reset values of the other settings: 4mA drive strength,
+        /// slow slew rate and Schmitt trigger enabled.
+        #[derive(Debug, Clone, Copy)]
+        pub struct PadConfig {
+            pull_up: bool,
+            drive_strength: OutputDriveStrength,
+            slew_rate: OutputSlewRate,
+            schmitt_trigger: bool,
+        }
+
+        impl Default for PadConfig {
+            fn default() -> Self {
+                Self {
+                    pull_up: true,
+                    drive_strength: OutputDriveStrength::FourMilliAmps,
+                    slew_rate: OutputSlewRate::Slow,
+                    schmitt_trigger: true,
+                }
+            }
+        }
+
+        impl PadConfig {
+            /// Enables the internal pull-up (otherwise, the pad has no pull).
+            #[must_use]
+            pub fn pull_up(mut self, enabled: bool) -> Self {
+                self.pull_up = enabled;
+                self
+            }
+
+            #[must_use]
+            pub fn drive_strength(mut self, drive_strength: OutputDriveStrength) -> Self {
+                self.drive_strength = drive_strength;
+                self
+            }
+
+            #[must_use]
+            pub fn slew_rate(mut self, slew_rate: OutputSlewRate) -> Self {
+                self.slew_rate = slew_rate;
+                self
+            }
+
+            /// Enables the Schmitt trigger (hysteresis) of the input.
+            #[must_use]
+            pub fn schmitt_trigger(mut self, enabled: bool) -> Self {
+                self.schmitt_trigger = enabled;
+                self
+            }
+        }
+
+        /// PIO transport, see [`DhtTransport`].
+        pub struct DhtPio<P: PIOExt, STI: StateMachineIndex, I: AnyPin<Function = P::PinFunction>> {
+            pio: hal::pio::PIO<P>,
+            pin: Pin<I::Id, P::PinFunction, DynPullType>,
             sm: StateMachine<(P, STI), Running>,
             rx_fifo: Rx<(P, STI)>,
             tx_fifo: Tx<(P, STI)>,
+            first_word: Option<u32>,
+            /// Error detected by [`Self::send_start_signal`], returned by the next poll.
+            error: Option<DhtError>,
+            conversion: Option<Conversion>,
+            /// Address of the instruction waiting for the start signal.
+            idle_address: u32,
+            /// Address of the first instruction receiving the data (after the ACK of the sensor).
+            data_address: u32,
+            timeout_phase: TimeoutPhase,
         }
 
-        #[allow(clippy::cast_possible_truncation)]
-        impl<const START_SIGNAL_LENGTH: u32, P: PIOExt, STI: StateMachineIndex>
-            DhtPio<START_SIGNAL_LENGTH, P, STI>
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        impl<P: PIOExt, STI: StateMachineIndex, I: AnyPin<Function = P::PinFunction>>
+            DhtPio<P, STI, I>
         {
-            pub(crate) fn new<I: AnyPin<Function = P::PinFunction>>(
+            /// Builds the transport alone, to wrap it (e.g. in [`Powered`](crate::power::Powered)) before giving it to a sensor.
+            ///
+            /// The pad of the pin is set up with the default [`PadConfig`].
+            pub fn new(
                 mut pio: hal::pio::PIO<P>,
                 sm: UninitStateMachine<(P, STI)>,
                 dht_pin: I,
@@ -31,13 +117,19 @@ macro_rules! define_dht {
             ) -> Self {
                 let program = pio_file!("./src/dht.pio");
 
-                let pin = dht_pin.into();
+                let pin: SpecificPin<I> = dht_pin.into();
+                let mut pin = pin.into_pull_type::<DynPullType>();
+                Self::configure_pad(&mut pin, PadConfig::default());
 
                 let installed = pio.install(&program.program).unwrap();
+                let idle_address =
+                    u32::from(installed.offset()) + program.public_defines.idle as u32;
+                let data_address =
+                    u32::from(installed.offset()) + program.public_defines.data as u32;
 
                 let (int, frac) = (clocks.system_clock.freq().to_MHz() as u16, 0);
                 assert!(int > 0, "the system_clock must be >= 1MHz");
-                
+
                 let (mut sm, rx, tx) = hal::pio::PIOBuilder::from_installed_program(installed)
                     .out_pins(pin.id().num, 1)
                     .set_pins(pin.id().num, 1)
@@ -50,63 +142,325 @@ macro_rules! define_dht {
                 sm.set_pindirs([(pin.id().num, hal::pio::PinDir::Output)]);
 
                 Self {
+                    pio,
+                    pin,
                     sm: sm.start(),
                     rx_fifo: rx,
                     tx_fifo: tx,
+                    first_word: None,
+                    error: None,
+                    conversion: None,
+                    idle_address,
+                    data_address,
+                    timeout_phase: TimeoutPhase::Response,
+                }
+            }
+
+            fn send_start_signal(&mut self, start_signal_length: u32) {
+                self.first_word = None;
+
+                // The line is driven high while idle
+                if !self.line_is_high() {
+                    self.error = Some(DhtError::LineStuckLow);
+                    return;
+                }
+
+                self.tx_fifo.write(start_signal_length - 1);
+            }
+
+            fn receive(&mut self) -> nb::Result<[u8; 5], DhtError> {
+                if let Some(e) = self.error.take() {
+                    return Err(nb::Error::Other(e));
+                }
+
+                while let Some(d) = self.rx_fifo.read() {
+                    match self.first_word.take() {
+                        None => self.first_word = Some(d),
+                        Some(data) => {
+                            let [b0, b1, b2, b3] = data.to_be_bytes();
+                            return Ok([b0, b1, b2, b3, d as u8]);
+                        }
+                    }
+                }
+
+                Err(nb::Error::WouldBlock)
+            }
+
+            /// Changes the pad settings of the pin.
+            #[must_use]
+            pub fn pad_config(mut self, config: PadConfig) -> Self {
+                Self::configure_pad(&mut self.pin, config);
+                self
+            }
+
+            fn configure_pad(pin: &mut Pin<I::Id, P::PinFunction, DynPullType>, config: PadConfig) {
+                pin.set_pull_type(if config.pull_up {
+                    DynPullType::Up
+                } else {
+                    DynPullType::None
+                });
+                pin.set_drive_strength(config.drive_strength);
+                pin.set_slew_rate(config.slew_rate);
+                pin.set_schmitt_enabled(config.schmitt_trigger);
+            }
+
+            /// Must be called [`LINE_SETTLE_TIME`] after [`Self::drive_low`].
+            fn timeout(&mut self) -> DhtError {
+                let stuck_high = self.line_is_high();
+                self.timeout_phase = if self.sm.instruction_address() >= self.data_address {
+                    TimeoutPhase::Data
+                } else {
+                    TimeoutPhase::Response
+                };
+                self.cancel();
+
+                if stuck_high {
+                    DhtError::LineStuckHigh
+                } else {
+                    DhtError::Timeout
+                }
+            }
+
+            pub(crate) fn recover<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), DhtError> {
+                self.cancel();
+
+                self.drive_low();
+                delay.delay_us(LINE_SETTLE_TIME);
+                if self.line_is_high() {
+                    self.cancel();
+                    return Err(DhtError::LineStuckHigh);
+                }
+
+                // Long enough to be taken as a start signal by any model
+                delay.delay_ms(RECOVERY_LOW_TIME);
+                self.release();
+
+                // Answer of the sensor to the start signal
+                delay.delay_ms(RECOVERY_ANSWER_TIME);
+                let stuck_low = !self.line_is_high();
+                self.cancel();
+
+                if stuck_low {
+                    Err(DhtError::LineStuckLow)
+                } else {
+                    Ok(())
+                }
+            }
+
+            /// Releases the line (the pin becomes an input), from any instruction of the program.
+            fn release(&mut self) {
+                self.sm.exec_instruction(pio::Instruction {
+                    operands: pio::InstructionOperands::SET {
+                        destination: pio::SetDestination::PINDIRS,
+                        data: 0,
+                    },
+                    delay: 0,
+                    side_set: None,
+                });
+            }
+
+            /// Drives the line low, from any instruction of the program.
+            fn drive_low(&mut self) {
+                for (destination, data) in [
+                    (pio::SetDestination::PINS, 0),
+                    (pio::SetDestination::PINDIRS, 1),
+                ] {
+                    self.sm.exec_instruction(pio::Instruction {
+                        operands: pio::InstructionOperands::SET { destination, data },
+                        delay: 0,
+                        side_set: None,
+                    });
                 }
             }
 
-            pub(crate) fn read_data<D: DelayNs>(&mut self, delay: &mut D) -> Result<(u16, u16), DhtError> {
-                let mut timeout = START_SIGNAL_LENGTH + 10;
-                let mut raw: [Option<u32>; 2] = [None; 2];
+            fn line_is_high(&self) -> bool {
+                self.pin.as_input().is_high().unwrap_or(false)
+            }
+
+            /// Stops the state machine, uninstalls the program and returns the PIO, the state machine and the pin.
+            ///
+            /// The pad of the pin is set back to its reset values (drive strength, slew rate and
+            /// Schmitt trigger), with the pull type of `I`.
+            pub fn free(self) -> (hal::pio::PIO<P>, UninitStateMachine<(P, STI)>, I) {
+                let (sm, program) = self.sm.stop().uninit(self.rx_fifo, self.tx_fifo);
+                let mut pio = self.pio;
+                pio.uninstall(program);
+
+                let mut pin = self.pin;
+                Self::configure_pad(&mut pin, PadConfig::default());
+
+                (pio, sm, I::from(pin.into_pull_type()))
+            }
+        }
+
+        impl<P: PIOExt, STI: StateMachineIndex, I: AnyPin<Function = P::PinFunction>> Transport
+            for DhtPio<P, STI, I>
+        {
+            fn timeout_phase(&self) -> TimeoutPhase {
+                self.timeout_phase
+            }
+
+            fn cancel(&mut self) {
+                self.first_word = None;
+                self.error = None;
+                self.conversion = None;
+                self.sm.clear_fifos();
+                // The program restarts from the beginning: the pin is driven high, waiting for the next start signal
+                self.sm.restart();
+                // Words pushed before the restart
+                while self.rx_fifo.read().is_some() {}
+            }
+
+            fn set_sensor_power(&mut self, powered: bool) {
+                self.cancel();
+
+                if !powered {
+                    // Once the program drives the line high, or it would overwrite the low level
+                    while self.sm.instruction_address() < self.idle_address {}
+                    self.drive_low();
+                }
+            }
+        }
 
-                self.tx_fifo.write(START_SIGNAL_LENGTH - 1);
+        impl<P: PIOExt, STI: StateMachineIndex, I: AnyPin<Function = P::PinFunction>> DhtTransport
+            for DhtPio<P, STI, I>
+        {
+            fn transfer<D: DelayNs>(
+                &mut self,
+                start_signal_length: u32,
+                delay: &mut D,
+            ) -> Result<[u8; 5], DhtError> {
+                let mut timeout = start_signal_length + ANSWER_TIMEOUT;
 
-                while timeout > 0 && raw[1].is_none() {
-                    match self.rx_fifo.read() {
-                        Some(d) => {
-                            if raw[0].is_none() {
-                                raw[0] = Some(d);
-                            } else {
-                                raw[1] = Some(d);
-                            }
+                self.send_start_signal(start_signal_length);
+
+                loop {
+                    match self.receive() {
+                        Err(nb::Error::WouldBlock) if timeout > 0 => (),
+                        Err(nb::Error::WouldBlock) => {
+                            self.drive_low();
+                            delay.delay_us(LINE_SETTLE_TIME);
+                            return Err(self.timeout());
                         }
-                        None => (),
+                        Err(nb::Error::Other(e)) => return Err(e),
+                        Ok(frame) => return Ok(frame),
                     }
 
                     delay.delay_ms(1);
                     timeout -= 1;
                 }
+            }
+        }
 
-                if timeout <= 0 {
-                    self.sm.restart();
-                    return Err(DhtError::Timeout);
+        impl<P: PIOExt, STI: StateMachineIndex, I: AnyPin<Function = P::PinFunction>>
+            NonBlockingTransport for DhtPio<P, STI, I>
+        {
+            fn start_conversion(
+                &mut self,
+                start_signal_length: u32,
+                now: Instant,
+                timeout: Duration,
+            ) {
+                // The frame of the conversion in progress is returned by the next polls
+                if self.conversion.is_some() {
+                    return;
                 }
 
-                let data = raw[0].unwrap();
-                let crc = raw[1].unwrap();
+                self.conversion = Some(Conversion::Running(now + timeout));
+                self.send_start_signal(start_signal_length);
+            }
+
+            fn poll(&mut self, now: Instant) -> nb::Result<[u8; 5], DhtError> {
+                self.poll_conversion(now)
+            }
+        }
+
+        /// Once the deadline is exceeded, the line is driven low to tell a dead sensor from a line
+        /// stuck high, the state machine is restarted and the error returned.
+        impl<P: PIOExt, STI: StateMachineIndex, I: AnyPin<Function = P::PinFunction>> Receiver
+            for DhtPio<P, STI, I>
+        {
+            fn conversion(&mut self) -> &mut Option<Conversion> {
+                &mut self.conversion
+            }
+
+            fn frame(&mut self, _now: Instant) -> nb::Result<[u8; 5], DhtError> {
+                self.receive()
+            }
+
+            fn expire(&mut self) -> Duration {
+                self.drive_low();
+                Duration::micros(LINE_SETTLE_TIME.into())
+            }
+
+            fn fail(&mut self) -> DhtError {
+                self.timeout()
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<P: PIOExt, STI: StateMachineIndex, I: AnyPin<Function = P::PinFunction>>
+            AsyncDhtTransport for DhtPio<P, STI, I>
+        {
+            async fn transfer_async<D: AsyncDelayNs>(
+                &mut self,
+                start_signal_length: u32,
+                delay: &mut D,
+            ) -> Result<[u8; 5], DhtError> {
+                let mut timeout = start_signal_length + ANSWER_TIMEOUT;
+
+                self.send_start_signal(start_signal_length);
+
+                loop {
+                    match self.receive() {
+                        Err(nb::Error::WouldBlock) if timeout > 0 => (),
+                        Err(nb::Error::WouldBlock) => {
+                            self.drive_low();
+                            delay.delay_us(LINE_SETTLE_TIME).await;
+                            return Err(self.timeout());
+                        }
+                        Err(nb::Error::Other(e)) => return Err(e),
+                        Ok(frame) => return Ok(frame),
+                    }
 
-                if Self::compute_crc(data) != crc {
-                    return Err(DhtError::CrcMismatch(
-                        raw[0].unwrap_or(0),
-                        raw[1].unwrap_or(0),
-                    ));
+                    delay.delay_ms(1).await;
+                    timeout -= 1;
                 }
+            }
+        }
 
-                Ok((
-                    (data & 0x0000FFFF) as u16,
-                    ((data & 0xFFFF0000) >> 16) as u16,
-                ))
+        impl<M: Model, P: PIOExt, STI: StateMachineIndex, I: AnyPin<Function = P::PinFunction>>
+            Sensor<M, DhtPio<P, STI, I>>
+        {
+            pub fn new(
+                pio: hal::pio::PIO<P>,
+                sm: UninitStateMachine<(P, STI)>,
+                dht_pin: I,
+                clocks: &hal::clocks::ClocksManager,
+            ) -> Self {
+                Self::from_transport(DhtPio::new(pio, sm, dht_pin, clocks))
             }
 
-            fn compute_crc(data: u32) -> u32 {
-                let mut crc: u32 = 0;
-                crc += data & 0x000000FF;
-                crc += (data & 0x0000FF00) >> 8;
-                crc += (data & 0x00FF0000) >> 16;
-                crc += (data & 0xFF000000) >> 24;
+            /// Changes the pad settings of the pin, see [`PadConfig`] for the default ones.
+            #[must_use]
+            pub fn pad_config(mut self, config: PadConfig) -> Self {
+                self.transport = self.transport.pad_config(config);
+                self
+            }
+
+            /// Checks the line and tries to recover it: drives it low for 20ms (seen as a start signal by the sensor,
+            /// which resets a latched one), releases it and checks that it is high once the sensor answered.
+            ///
+            /// Returns [`DhtError::LineStuckHigh`] if the line cannot be driven low (shorted to VCC), or
+            /// [`DhtError::LineStuckLow`] if it stays low (shorted to ground, or a sensor holding the line).
+            #[allow(clippy::missing_errors_doc)]
+            pub fn recover<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), DhtError> {
+                self.transport.recover(delay)
+            }
 
-                crc % 256
+            /// Stops the state machine, uninstalls the program and returns the PIO, the state machine and the pin.
+            pub fn free(self) -> (hal::pio::PIO<P>, UninitStateMachine<(P, STI)>, I) {
+                self.transport.free()
             }
         }
     };
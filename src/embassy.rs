@@ -0,0 +1,190 @@
+use embassy_rp::clocks::clk_sys_freq;
+use embassy_rp::pio::{
+    Common, Config, Direction, Instance, PioPin, ShiftConfig, ShiftDirection, StateMachine,
+};
+use embassy_rp::Peripheral;
+use embassy_time::{with_timeout, Duration};
+use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
+use fixed::traits::ToFixed;
+use pio_proc::pio_file;
+
+use crate::model::Model;
+use crate::sensor::ANSWER_TIMEOUT;
+use crate::stats::TimeoutPhase;
+use crate::{AsyncDhtTransport, DhtError, Sensor, Transport};
+
+/// embassy-rp PIO transport.
+pub struct DhtPio<'d, PIO: Instance, const SM: usize> {
+    sm: StateMachine<'d, PIO, SM>,
+    /// Address of the first instruction of the program.
+    origin: u8,
+    timeout_phase: TimeoutPhase,
+}
+
+impl<'d, PIO: Instance, const SM: usize> DhtPio<'d, PIO, SM> {
+    /// Builds the transport alone, to wrap it (e.g. in [`Powered`](crate::power::Powered)) before giving it to a sensor.
+    pub fn new(
+        common: &mut Common<'d, PIO>,
+        mut sm: StateMachine<'d, PIO, SM>,
+        dht_pin: impl Peripheral<P = impl PioPin + 'd> + 'd,
+    ) -> Self {
+        let program = pio_file!("./src/dht.pio");
+        let installed = common.load_program(&program.program);
+        let pin = common.make_pio_pin(dht_pin);
+
+        let int = clk_sys_freq() / 1_000_000;
+        assert!(int > 0, "the system_clock must be >= 1MHz");
+
+        let shift = ShiftConfig {
+            threshold: 32,
+            direction: ShiftDirection::Left,
+            auto_fill: false,
+        };
+
+        let mut cfg = Config::default();
+        cfg.use_program(&installed, &[]);
+        cfg.set_out_pins(&[&pin]);
+        cfg.set_set_pins(&[&pin]);
+        cfg.set_in_pins(&[&pin]);
+        cfg.clock_divider = int.to_fixed();
+        cfg.shift_in = shift;
+        cfg.shift_out = shift;
+
+        sm.set_config(&cfg);
+        sm.set_pin_dirs(Direction::Out, &[&pin]);
+        sm.set_enable(true);
+
+        Self {
+            sm,
+            origin: installed.origin,
+            timeout_phase: TimeoutPhase::Response,
+        }
+    }
+
+    /// Phase of the stopped program, from its Y register: the byte counter (4 to 0) once the sensor
+    /// has answered, the start signal counter (underflowed to `u32::MAX`) before.
+    fn phase(&mut self) -> TimeoutPhase {
+        let side_set = pio::SideSet::default();
+        let mov = pio::Instruction {
+            operands: pio::InstructionOperands::MOV {
+                destination: pio::MovDestination::ISR,
+                op: pio::MovOperation::None,
+                source: pio::MovSource::Y,
+            },
+            delay: 0,
+            side_set: None,
+        };
+        let push = pio::Instruction {
+            operands: pio::InstructionOperands::PUSH {
+                if_full: false,
+                block: false,
+            },
+            delay: 0,
+            side_set: None,
+        };
+
+        self.sm.clear_fifos();
+        // Safety: the state machine is stopped, and restarted by `cancel` at the beginning of the program
+        unsafe {
+            self.sm.exec_instr(mov.encode(side_set));
+            self.sm.exec_instr(push.encode(side_set));
+        }
+
+        match self.sm.rx().try_pull() {
+            Some(y) if y <= 4 => TimeoutPhase::Data,
+            _ => TimeoutPhase::Response,
+        }
+    }
+}
+
+impl<'d, PIO: Instance, const SM: usize> Transport for DhtPio<'d, PIO, SM> {
+    fn timeout_phase(&self) -> TimeoutPhase {
+        self.timeout_phase
+    }
+
+    fn cancel(&mut self) {
+        self.sm.set_enable(false);
+        self.sm.clear_fifos();
+        // Clears the bits of an aborted frame left in the ISR, and the shift counters
+        self.sm.restart();
+
+        let jmp = pio::Instruction {
+            operands: pio::InstructionOperands::JMP {
+                condition: pio::JmpCondition::Always,
+                address: self.origin,
+            },
+            delay: 0,
+            side_set: None,
+        };
+        // Safety: jumps to the beginning of the program, which drives the pin high and waits for the next start signal
+        unsafe { self.sm.exec_instr(jmp.encode(pio::SideSet::default())) };
+        self.sm.set_enable(true);
+    }
+
+    fn set_sensor_power(&mut self, powered: bool) {
+        if powered {
+            self.cancel();
+            return;
+        }
+
+        // Stopped until the sensor is powered on again
+        self.sm.set_enable(false);
+        self.sm.clear_fifos();
+        for (destination, data) in [
+            (pio::SetDestination::PINS, 0),
+            (pio::SetDestination::PINDIRS, 1),
+        ] {
+            let set = pio::Instruction {
+                operands: pio::InstructionOperands::SET { destination, data },
+                delay: 0,
+                side_set: None,
+            };
+            // Safety: drives the line low, the program is restarted from its beginning on power on
+            unsafe { self.sm.exec_instr(set.encode(pio::SideSet::default())) };
+        }
+    }
+}
+
+impl<'d, PIO: Instance, const SM: usize> AsyncDhtTransport for DhtPio<'d, PIO, SM> {
+    /// The delay is not used, the timeout is measured with embassy-time.
+    #[allow(clippy::cast_possible_truncation)]
+    async fn transfer_async<D: AsyncDelayNs>(
+        &mut self,
+        start_signal_length: u32,
+        _delay: &mut D,
+    ) -> Result<[u8; 5], DhtError> {
+        let timeout = Duration::from_millis(u64::from(start_signal_length + ANSWER_TIMEOUT));
+
+        self.sm.tx().wait_push(start_signal_length - 1).await;
+
+        let frame = with_timeout(timeout, async {
+            let data = self.sm.rx().wait_pull().await;
+            let crc = self.sm.rx().wait_pull().await;
+            (data, crc)
+        })
+        .await;
+
+        match frame {
+            Ok((data, crc)) => {
+                let [b0, b1, b2, b3] = data.to_be_bytes();
+                Ok([b0, b1, b2, b3, crc as u8])
+            }
+            Err(_) => {
+                self.sm.set_enable(false);
+                self.timeout_phase = self.phase();
+                self.cancel();
+                Err(DhtError::Timeout)
+            }
+        }
+    }
+}
+
+impl<'d, M: Model, PIO: Instance, const SM: usize> Sensor<M, DhtPio<'d, PIO, SM>> {
+    pub fn new(
+        common: &mut Common<'d, PIO>,
+        sm: StateMachine<'d, PIO, SM>,
+        dht_pin: impl Peripheral<P = impl PioPin + 'd> + 'd,
+    ) -> Self {
+        Self::from_transport(DhtPio::new(common, sm, dht_pin))
+    }
+}
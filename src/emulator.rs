@@ -0,0 +1,114 @@
+//! Emulation of a DHT sensor, to test a host (this crate running on another board, or any other driver).
+//!
+//! The emulator uses its own program and needs a free state machine on a PIO block which does not
+//! run the [`DhtPio`](crate::DhtPio) program.
+
+use pio_proc::pio_file;
+
+use crate::dht::hal;
+use hal::gpio::{AnyPin, SpecificPin};
+use hal::pio::{MovStatusConfig, PIOExt, Running, StateMachine, StateMachineIndex, Tx};
+use hal::pio::{Rx, ShiftDirection, UninitStateMachine};
+use hal::Clock;
+
+use crate::{encode, Dht22Result};
+
+/// Emulated DHT sensor, answering the start signals of the host with the last reading set.
+///
+/// The emulator buffers a single frame, [`Self::update`] must be called between two reads of the host
+/// (without frame, the start signal is not answered).
+pub struct DhtEmulator<P: PIOExt, STI: StateMachineIndex, I: AnyPin<Function = P::PinFunction>> {
+    pio: hal::pio::PIO<P>,
+    pin: SpecificPin<I>,
+    sm: StateMachine<(P, STI), Running>,
+    rx_fifo: Rx<(P, STI)>,
+    tx_fifo: Tx<(P, STI)>,
+    /// Address of the first instruction answering the host.
+    answer: u32,
+    frame: Option<[u8; 5]>,
+}
+
+impl<P: PIOExt, STI: StateMachineIndex, I: AnyPin<Function = P::PinFunction>>
+    DhtEmulator<P, STI, I>
+{
+    /// The sensor does not answer until a reading is set.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn new(
+        mut pio: hal::pio::PIO<P>,
+        sm: UninitStateMachine<(P, STI)>,
+        pin: I,
+        clocks: &hal::clocks::ClocksManager,
+    ) -> Self {
+        let program = pio_file!("./src/emulator.pio");
+
+        let pin: SpecificPin<I> = pin.into();
+
+        let installed = pio.install(&program.program).unwrap();
+        let answer = u32::from(installed.offset()) + program.public_defines.answer as u32;
+
+        let (int, frac) = (clocks.system_clock.freq().to_MHz() as u16, 0);
+        assert!(int > 0, "the system_clock must be >= 1MHz");
+
+        let (sm, rx, tx) = hal::pio::PIOBuilder::from_installed_program(installed)
+            .set_pins(pin.id().num, 1)
+            .in_pin_base(pin.id().num)
+            .jmp_pin(pin.id().num)
+            .clock_divisor_fixed_point(int, frac)
+            .pull_threshold(32)
+            // `mov x, status` tells whether a frame is buffered
+            .set_mov_status_config(MovStatusConfig::Tx(1))
+            .out_shift_direction(ShiftDirection::Left)
+            .build(sm);
+
+        Self {
+            pio,
+            pin,
+            sm: sm.start(),
+            rx_fifo: rx,
+            tx_fifo: tx,
+            answer,
+            frame: None,
+        }
+    }
+
+    /// Sets the reading sent to the host, encoded like a DHT22.
+    pub fn set_reading(&mut self, reading: &Dht22Result) {
+        self.set_frame(encode::dht22(reading));
+    }
+
+    /// Sets the raw frame sent to the host, see [`encode`] for the other models.
+    ///
+    /// If the host is already reading, it gets the previous frame and the new one is sent on the next read.
+    pub fn set_frame(&mut self, frame: [u8; 5]) {
+        self.frame = Some(frame);
+
+        // The program pulls the frame once the start signal is over: until then, the buffered frame can be replaced.
+        if self.sm.instruction_address() < self.answer {
+            self.sm.clear_fifos();
+        }
+
+        self.update();
+    }
+
+    /// Buffers the current frame for the next read of the host.
+    pub fn update(&mut self) {
+        let Some(frame) = self.frame else {
+            return;
+        };
+
+        if self.tx_fifo.is_empty() {
+            let [b0, b1, b2, b3, crc] = frame;
+            self.tx_fifo.write(u32::from_be_bytes([b0, b1, b2, b3]));
+            self.tx_fifo.write(u32::from(crc));
+        }
+    }
+
+    /// Stops the state machine, uninstalls the program and returns the PIO, the state machine and the pin.
+    pub fn free(self) -> (hal::pio::PIO<P>, UninitStateMachine<(P, STI)>, I) {
+        let (sm, program) = self.sm.stop().uninit(self.rx_fifo, self.tx_fifo);
+        let mut pio = self.pio;
+        pio.uninstall(program);
+
+        (pio, sm, I::from(self.pin))
+    }
+}
@@ -0,0 +1,66 @@
+//! Frame encoding, the inverse of the decoding done by the sensors.
+//!
+//! Values out of the range of the sensor are clamped.
+
+use crate::decode;
+use crate::{Dht11Result, Dht22Result};
+
+/// Encodes a reading as sent by a [`Dht22`](crate::Dht22) (sign and magnitude temperature).
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn dht22(reading: &Dht22Result) -> [u8; 5] {
+    let temperature = reading.temperature * 10.0;
+    let mut raw_temp = (abs(temperature) + 0.5).min(f32::from(0x7FFF_u16)) as u16;
+
+    if temperature <= -0.5 {
+        raw_temp |= 0x8000;
+    }
+
+    raw_frame(raw_temp, encode_humidity(reading.humidity))
+}
+
+/// Encodes a reading as sent by a [`Dht22Type2`](crate::Dht22Type2) (two's complement temperature).
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn dht22_type2(reading: &Dht22Result) -> [u8; 5] {
+    let temperature = reading.temperature * 10.0;
+    let rounded = if temperature < 0.0 {
+        temperature - 0.5
+    } else {
+        temperature + 0.5
+    };
+
+    raw_frame(rounded as i16 as u16, encode_humidity(reading.humidity))
+}
+
+/// Encodes a reading as sent by a [`Dht11`](crate::Dht11).
+pub fn dht11(reading: &Dht11Result) -> [u8; 5] {
+    let temperature = reading.temperature.min(0xFF);
+    let raw_temp = if temperature <= 0x7F {
+        temperature << 8
+    } else {
+        0x8000 | ((0xFF - temperature) << 8)
+    };
+
+    raw_frame(raw_temp, reading.humidity.min(0xFF) << 8)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn encode_humidity(humidity: f32) -> u16 {
+    (humidity * 10.0 + 0.5).clamp(0.0, f32::from(u16::MAX)) as u16
+}
+
+fn abs(value: f32) -> f32 {
+    if value < 0.0 {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Builds the frame (with its checksum) of the raw temperature and humidity words.
+#[allow(clippy::cast_possible_truncation)]
+pub fn raw_frame(raw_temp: u16, raw_hum: u16) -> [u8; 5] {
+    let data = (u32::from(raw_hum) << 16) | u32::from(raw_temp);
+    let [b0, b1, b2, b3] = data.to_be_bytes();
+
+    [b0, b1, b2, b3, decode::compute_crc(data) as u8]
+}
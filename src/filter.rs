@@ -0,0 +1,263 @@
+//! Filters over a stream of readings, for both the float (DHT22) and integer (DHT11) readings.
+//!
+//! Filters are chained with [`Filter::then`], for example a spike rejection followed by a median:
+//! ```ignore
+//! let mut filter = RateLimit::new(2.0, 5.0).then(Median::<f32, f32, 5>::new());
+//! if let Some(reading) = filter.update(dht.read(&mut delay)?) {
+//!     // ...
+//! }
+//! ```
+
+use crate::DhtResult;
+
+/// Value of a reading (temperature or humidity).
+pub trait Value: Copy + PartialOrd + Default {
+    /// Moves `self` toward `target` by `weight` / 256.
+    fn approach(self, target: Self, weight: u16) -> Self;
+    /// Absolute difference between `self` and `other`.
+    fn distance(self, other: Self) -> Self;
+    fn to_f32(self) -> f32;
+    /// Rounded (and saturated) for the integer values.
+    fn from_f32(value: f32) -> Self;
+}
+
+impl Value for f32 {
+    fn approach(self, target: Self, weight: u16) -> Self {
+        self + (target - self) * f32::from(weight) / 256.0
+    }
+
+    fn distance(self, other: Self) -> Self {
+        if self > other {
+            self - other
+        } else {
+            other - self
+        }
+    }
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+impl Value for u16 {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn approach(self, target: Self, weight: u16) -> Self {
+        let delta = i32::from(target) - i32::from(self);
+        // Rounded away from zero, so that the output always reaches the target
+        let step = (delta.abs() * i32::from(weight) + 255) / 256;
+        (i32::from(self) + step * delta.signum()) as u16
+    }
+
+    fn distance(self, other: Self) -> Self {
+        self.abs_diff(other)
+    }
+
+    fn to_f32(self) -> f32 {
+        f32::from(self)
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn from_f32(value: f32) -> Self {
+        // `as` saturates
+        libm::roundf(value) as u16
+    }
+}
+
+pub trait Filter<T: Value, H: Value> {
+    /// Feeds a reading, and returns the filtered one (`None` if the reading is rejected).
+    fn update(&mut self, reading: DhtResult<T, H>) -> Option<DhtResult<T, H>>;
+
+    /// Forgets the previous readings.
+    fn reset(&mut self);
+
+    /// Feeds the output of this filter to `next`.
+    fn then<F: Filter<T, H>>(self, next: F) -> Chain<Self, F>
+    where
+        Self: Sized,
+    {
+        Chain {
+            first: self,
+            second: next,
+        }
+    }
+}
+
+/// Two filters in series, see [`Filter::then`].
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<T: Value, H: Value, A: Filter<T, H>, B: Filter<T, H>> Filter<T, H> for Chain<A, B> {
+    fn update(&mut self, reading: DhtResult<T, H>) -> Option<DhtResult<T, H>> {
+        self.first
+            .update(reading)
+            .and_then(|reading| self.second.update(reading))
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+    }
+}
+
+/// Median of the last `N` readings (temperature and humidity taken separately).
+///
+/// Until `N` readings are received, the median of the readings received so far is returned. With
+/// an even number of readings, the lower median is returned.
+pub struct Median<T: Value, H: Value, const N: usize> {
+    temperatures: [T; N],
+    humidities: [H; N],
+    /// Index of the next reading in the buffers.
+    next: usize,
+    len: usize,
+}
+
+impl<T: Value, H: Value, const N: usize> Median<T, H, N> {
+    pub fn new() -> Self {
+        assert!(N > 0, "the median needs at least one reading");
+
+        Self {
+            temperatures: [T::default(); N],
+            humidities: [H::default(); N],
+            next: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<T: Value, H: Value, const N: usize> Default for Median<T, H, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Value, H: Value, const N: usize> Filter<T, H> for Median<T, H, N> {
+    fn update(&mut self, reading: DhtResult<T, H>) -> Option<DhtResult<T, H>> {
+        self.temperatures[self.next] = reading.temperature;
+        self.humidities[self.next] = reading.humidity;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+
+        Some(DhtResult {
+            temperature: median(self.temperatures, self.len),
+            humidity: median(self.humidities, self.len),
+        })
+    }
+
+    fn reset(&mut self) {
+        self.next = 0;
+        self.len = 0;
+    }
+}
+
+/// Lower median of the `len` first values.
+fn median<V: Value, const N: usize>(mut values: [V; N], len: usize) -> V {
+    let values = &mut values[..len];
+
+    // Insertion sort, N is small
+    for i in 1..values.len() {
+        let mut j = i;
+        while j > 0 && values[j - 1] > values[j] {
+            values.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+
+    values[(len - 1) / 2]
+}
+
+/// Exponential moving average: each reading moves the output by `weight` / 256 of its difference with it.
+pub struct Ema<T: Value, H: Value> {
+    weight: u16,
+    last: Option<DhtResult<T, H>>,
+}
+
+impl<T: Value, H: Value> Ema<T, H> {
+    /// `weight` (from 1 to 256) is the weight of a new reading, in 1/256.
+    pub fn new(weight: u16) -> Self {
+        assert!(
+            (1..=256).contains(&weight),
+            "the weight must be between 1 and 256"
+        );
+
+        Self { weight, last: None }
+    }
+}
+
+impl<T: Value, H: Value> Filter<T, H> for Ema<T, H> {
+    fn update(&mut self, reading: DhtResult<T, H>) -> Option<DhtResult<T, H>> {
+        let average = match self.last {
+            None => reading,
+            Some(last) => DhtResult {
+                temperature: last.temperature.approach(reading.temperature, self.weight),
+                humidity: last.humidity.approach(reading.humidity, self.weight),
+            },
+        };
+
+        self.last = Some(average);
+        Some(average)
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Rejects the readings too far from the last accepted one (physically impossible jumps between two reads).
+///
+/// After a few consecutive rejections, the new level is accepted (the previous reading may have been the wrong one).
+pub struct RateLimit<T: Value, H: Value> {
+    max_temperature_step: T,
+    max_humidity_step: H,
+    accept_after: u8,
+    rejections: u8,
+    last: Option<DhtResult<T, H>>,
+}
+
+impl<T: Value, H: Value> RateLimit<T, H> {
+    /// Largest change of temperature and humidity allowed between two readings.
+    pub fn new(max_temperature_step: T, max_humidity_step: H) -> Self {
+        Self {
+            max_temperature_step,
+            max_humidity_step,
+            accept_after: 3,
+            rejections: 0,
+            last: None,
+        }
+    }
+
+    /// Number of consecutive rejections after which the reading is accepted (3 by default).
+    #[must_use]
+    pub fn accept_after(mut self, rejections: u8) -> Self {
+        self.accept_after = rejections;
+        self
+    }
+}
+
+impl<T: Value, H: Value> Filter<T, H> for RateLimit<T, H> {
+    fn update(&mut self, reading: DhtResult<T, H>) -> Option<DhtResult<T, H>> {
+        if let Some(last) = self.last {
+            let jump = last.temperature.distance(reading.temperature) > self.max_temperature_step
+                || last.humidity.distance(reading.humidity) > self.max_humidity_step;
+
+            if jump && self.rejections < self.accept_after {
+                self.rejections += 1;
+                return None;
+            }
+        }
+
+        self.rejections = 0;
+        self.last = Some(reading);
+        Some(reading)
+    }
+
+    fn reset(&mut self) {
+        self.rejections = 0;
+        self.last = None;
+    }
+}
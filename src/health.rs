@@ -0,0 +1,158 @@
+//! Health of a sensor: backoff between the attempts of a failing sensor, and quarantine of a dead one.
+//!
+//! ```text
+//! Healthy --(N failures)--> Degraded --(M failures)--> Quarantined
+//!    ^                          |                           |
+//!    +---------(success)--------+-----------(success)-------+
+//! ```
+
+use crate::{DhtError, Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HealthState {
+    /// Read at every polling cycle.
+    Healthy,
+    /// Read with an exponential backoff between the attempts.
+    Degraded,
+    /// Only probed from time to time.
+    Quarantined,
+}
+
+/// Change of the state of a sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Transition {
+    pub from: HealthState,
+    pub to: HealthState,
+}
+
+/// Health state machine of a sensor, fed with the result of each read.
+///
+/// ```ignore
+/// if health.should_read(timer.now()) {
+///     let result = dht.read(&mut delay);
+///     if let Some(transition) = health.record(&result, timer.now()) {
+///         // report the transition
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Health {
+    degraded_after: u32,
+    quarantined_after: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    probe_interval: Duration,
+
+    state: HealthState,
+    consecutive_failures: u32,
+    next_attempt: Option<Instant>,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            degraded_after: 3,
+            quarantined_after: 10,
+            initial_backoff: Duration::secs(2),
+            max_backoff: Duration::secs(64),
+            probe_interval: Duration::secs(300),
+            state: HealthState::Healthy,
+            consecutive_failures: 0,
+            next_attempt: None,
+        }
+    }
+}
+
+impl Health {
+    /// Degraded after 3 consecutive failures, with a backoff from 2s to 64s, quarantined after 10
+    /// consecutive failures and then probed every 5 minutes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of consecutive failures after which the sensor is degraded.
+    #[must_use]
+    pub fn degraded_after(mut self, failures: u32) -> Self {
+        self.degraded_after = failures;
+        self
+    }
+
+    /// Number of consecutive failures after which the sensor is quarantined.
+    #[must_use]
+    pub fn quarantined_after(mut self, failures: u32) -> Self {
+        self.quarantined_after = failures;
+        self
+    }
+
+    /// Backoff after the first failure of a degraded sensor, doubled on each new failure up to `max`.
+    #[must_use]
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Time between two probes of a quarantined sensor.
+    #[must_use]
+    pub fn probe_interval(mut self, interval: Duration) -> Self {
+        self.probe_interval = interval;
+        self
+    }
+
+    pub fn state(&self) -> HealthState {
+        self.state
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// Time of the next attempt, `None` if the sensor can be read at any time.
+    pub fn next_attempt(&self) -> Option<Instant> {
+        self.next_attempt
+    }
+
+    /// Returns `true` if the sensor should be read at `now`.
+    pub fn should_read(&self, now: Instant) -> bool {
+        self.next_attempt.is_none_or(|next| now >= next)
+    }
+
+    /// Records the result of a read made at `now`, and returns the transition if the state changed.
+    pub fn record<R>(&mut self, result: &Result<R, DhtError>, now: Instant) -> Option<Transition> {
+        let from = self.state;
+
+        if result.is_ok() {
+            self.consecutive_failures = 0;
+            self.state = HealthState::Healthy;
+            self.next_attempt = None;
+        } else {
+            self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+
+            if self.consecutive_failures >= self.quarantined_after {
+                self.state = HealthState::Quarantined;
+                self.next_attempt = Some(now + self.probe_interval);
+            } else if self.consecutive_failures >= self.degraded_after {
+                self.state = HealthState::Degraded;
+                self.next_attempt = Some(now + self.current_backoff());
+            }
+        }
+
+        (from != self.state).then_some(Transition {
+            from,
+            to: self.state,
+        })
+    }
+
+    /// Backoff after the last failure of a degraded sensor.
+    fn current_backoff(&self) -> Duration {
+        let doublings = self.consecutive_failures - self.degraded_after;
+        let backoff = self
+            .initial_backoff
+            .ticks()
+            .saturating_mul(1_u64.checked_shl(doublings).unwrap_or(u64::MAX));
+
+        Duration::from_ticks(backoff.min(self.max_backoff.ticks()))
+    }
+}
@@ -0,0 +1,265 @@
+//! History of the last readings, with their statistics.
+
+use crate::filter::Value;
+use crate::{DhtResult, Duration, Instant};
+
+/// Reading and the time it was made.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sample<T, H> {
+    #[cfg_attr(feature = "serde", serde(with = "ticks"))]
+    pub at: Instant,
+    pub reading: DhtResult<T, H>,
+}
+
+/// Statistics of the temperature or the humidity over a window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Summary<V> {
+    pub min: V,
+    pub max: V,
+    pub mean: f32,
+    /// Population standard deviation.
+    pub std_dev: f32,
+}
+
+/// Ring buffer of the last `N` readings, for the DHT22 (`History<N>`) or the DHT11 (`History<N, u16, u16>`).
+///
+/// With the `serde` feature, the history is serialized as its sampling interval (in µs) and its samples (oldest first).
+#[derive(Debug, Clone)]
+pub struct History<const N: usize, T: Value = f32, H: Value = f32> {
+    samples: [Sample<T, H>; N],
+    /// Index of the next sample in the buffer.
+    next: usize,
+    len: usize,
+    interval: Duration,
+}
+
+impl<const N: usize, T: Value, H: Value> History<N, T, H> {
+    /// `interval` is the expected time between two readings, used to count the missing ones.
+    pub fn new(interval: Duration) -> Self {
+        assert!(N > 0, "the history needs at least one sample");
+
+        Self {
+            samples: [Sample {
+                at: Instant::from_ticks(0),
+                reading: DhtResult {
+                    temperature: T::default(),
+                    humidity: H::default(),
+                },
+            }; N],
+            next: 0,
+            len: 0,
+            interval,
+        }
+    }
+
+    /// Adds a reading, replacing the oldest one if the history is full.
+    pub fn push(&mut self, reading: DhtResult<T, H>, at: Instant) {
+        self.samples[self.next] = Sample { at, reading };
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    pub fn clear(&mut self) {
+        self.next = 0;
+        self.len = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn latest(&self) -> Option<&Sample<T, H>> {
+        self.iter().next_back()
+    }
+
+    /// Samples, from the oldest to the latest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Sample<T, H>> + Clone + '_ {
+        let start = (self.next + N - self.len) % N;
+        (0..self.len).map(move |i| &self.samples[(start + i) % N])
+    }
+
+    /// Statistics over the whole history.
+    pub fn summary(&self) -> Option<DhtResult<Summary<T>, Summary<H>>> {
+        summarize(self.iter())
+    }
+
+    /// Statistics over the samples made at or after `since`.
+    pub fn summary_since(&self, since: Instant) -> Option<DhtResult<Summary<T>, Summary<H>>> {
+        summarize(self.iter().filter(|s| s.at >= since))
+    }
+
+    /// Statistics (and extremes) of the last hour.
+    pub fn last_hour(&self, now: Instant) -> Option<DhtResult<Summary<T>, Summary<H>>> {
+        let since = now
+            .checked_sub_duration(Duration::secs(3600))
+            .unwrap_or(Instant::from_ticks(0));
+        self.summary_since(since)
+    }
+
+    /// Number of readings missing between the samples of the history, given the sampling interval.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn missing(&self) -> u32 {
+        let interval = self.interval.ticks();
+        if interval == 0 {
+            return 0;
+        }
+
+        self.iter()
+            .zip(self.iter().skip(1))
+            .map(|(a, b)| {
+                let gap = b.at.checked_duration_since(a.at).map_or(0, |d| d.ticks());
+                // Rounded to the nearest number of intervals
+                ((gap + interval / 2) / interval).saturating_sub(1) as u32
+            })
+            .fold(0_u32, u32::saturating_add)
+    }
+}
+
+fn summarize<'a, T: Value + 'a, H: Value + 'a>(
+    samples: impl Iterator<Item = &'a Sample<T, H>> + Clone,
+) -> Option<DhtResult<Summary<T>, Summary<H>>> {
+    Some(DhtResult {
+        temperature: summarize_values(samples.clone().map(|s| s.reading.temperature))?,
+        humidity: summarize_values(samples.map(|s| s.reading.humidity))?,
+    })
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn summarize_values<V: Value>(values: impl Iterator<Item = V> + Clone) -> Option<Summary<V>> {
+    let mut count = 0_u32;
+    let mut sum = 0.0;
+    let mut min: Option<V> = None;
+    let mut max: Option<V> = None;
+
+    for value in values.clone() {
+        count += 1;
+        sum += value.to_f32();
+        min = Some(min.map_or(value, |min| if value < min { value } else { min }));
+        max = Some(max.map_or(value, |max| if value > max { value } else { max }));
+    }
+
+    let mean = sum / count as f32;
+    let variance = values
+        .map(|v| (v.to_f32() - mean) * (v.to_f32() - mean))
+        .sum::<f32>()
+        / count as f32;
+
+    Some(Summary {
+        min: min?,
+        max: max?,
+        mean,
+        std_dev: libm::sqrtf(variance),
+    })
+}
+
+#[cfg(feature = "serde")]
+mod ticks {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::Instant;
+
+    pub fn serialize<S: Serializer>(at: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(at.ticks())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+        Ok(Instant::from_ticks(u64::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::{SeqAccess, Visitor};
+    use serde::ser::SerializeStruct;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{History, Sample};
+    use crate::filter::Value;
+    use crate::Duration;
+
+    struct Samples<'a, const N: usize, T: Value, H: Value>(&'a History<N, T, H>);
+
+    impl<const N: usize, T: Value + Serialize, H: Value + Serialize> Serialize
+        for Samples<'_, N, T, H>
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.0.iter())
+        }
+    }
+
+    impl<const N: usize, T: Value + Serialize, H: Value + Serialize> Serialize for History<N, T, H> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut state = serializer.serialize_struct("History", 2)?;
+            state.serialize_field("interval_us", &self.interval.ticks())?;
+            state.serialize_field("samples", &Samples(self))?;
+            state.end()
+        }
+    }
+
+    /// Samples pushed into an empty history (the oldest ones are dropped if there are more than `N`).
+    struct Filled<const N: usize, T: Value, H: Value>(History<N, T, H>);
+
+    impl<'de, const N: usize, T: Value + Deserialize<'de>, H: Value + Deserialize<'de>>
+        Deserialize<'de> for Filled<N, T, H>
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct SamplesVisitor<const N: usize, T, H>(PhantomData<(T, H)>);
+
+            impl<'de, const N: usize, T: Value + Deserialize<'de>, H: Value + Deserialize<'de>>
+                Visitor<'de> for SamplesVisitor<N, T, H>
+            {
+                type Value = Filled<N, T, H>;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a sequence of samples")
+                }
+
+                fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                    let mut history = History::new(Duration::from_ticks(0));
+                    while let Some(sample) = seq.next_element::<Sample<T, H>>()? {
+                        history.push(sample.reading, sample.at);
+                    }
+                    Ok(Filled(history))
+                }
+            }
+
+            deserializer.deserialize_seq(SamplesVisitor(PhantomData))
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(bound(deserialize = "T: Value + Deserialize<'de>, H: Value + Deserialize<'de>"))]
+    struct Raw<const N: usize, T: Value, H: Value> {
+        interval_us: u64,
+        samples: Filled<N, T, H>,
+    }
+
+    impl<'de, const N: usize, T: Value + Deserialize<'de>, H: Value + Deserialize<'de>>
+        Deserialize<'de> for History<N, T, H>
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let raw = Raw::<N, T, H>::deserialize(deserializer)?;
+            let mut history = raw.samples.0;
+            history.interval = Duration::from_ticks(raw.interval_us);
+            Ok(history)
+        }
+    }
+}
@@ -1,10 +1,18 @@
 #![no_std]
 
+#[cfg(any(feature = "rp2040", feature = "rp235x"))]
 #[macro_use]
 mod dht_common;
-
-use embedded_hal::delay::DelayNs;
-
+#[cfg(any(feature = "rp2040", feature = "rp235x", feature = "mock"))]
+mod conversion;
+mod decode;
+pub mod encode;
+pub mod filter;
+pub mod health;
+pub mod history;
+pub mod model;
+
+#[cfg(any(feature = "rp2040", feature = "rp235x"))]
 mod dht {
     #[cfg(feature = "rp2040")]
     define_dht!(rp2040_hal);
@@ -13,14 +21,30 @@ mod dht {
     define_dht!(rp235x_hal);
 }
 
-use dht::hal::{
-    self,
-    gpio::AnyPin,
-    pio::{PIOExt, StateMachineIndex, UninitStateMachine},
-};
-use dht::DhtPio;
+mod sensor;
+pub use sensor::{Dht11, Dht22, Dht22Type2, Sensor};
+
+#[cfg(any(feature = "rp2040", feature = "rp235x"))]
+pub use dht::{DhtPio, PadConfig};
+
+pub mod alarm;
+pub mod bitbang;
+pub mod calibration;
+pub mod comfort;
+pub mod condensation;
+#[cfg(feature = "embassy-rp")]
+pub mod embassy;
+#[cfg(any(feature = "rp2040", feature = "rp235x"))]
+pub mod emulator;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod power;
+pub mod stats;
+pub mod trend;
+
+use embedded_hal::delay::DelayNs;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DhtError {
     /// Timeout during communication.
@@ -29,107 +53,133 @@ pub enum DhtError {
     CrcMismatch(u32, u32),
     /// FIFO Read error
     ReadError,
+    /// GPIO error (bit-banged backend).
+    PinError,
+    /// The line is low before the start signal (shorted to ground, or a sensor holding it).
+    LineStuckLow,
+    /// The line stays high while the start signal drives it low (shorted to VCC).
+    LineStuckHigh,
+    /// Reading out of the range of the sensor (only when the range check is enabled).
+    OutOfRange,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DhtResult<T, H> {
     pub temperature: T,
     pub humidity: H,
 }
 
-type Dht22Result = DhtResult<f32, f32>;
-type Dht11Result = DhtResult<u16, u16>;
+impl<T: filter::Value, H: filter::Value> DhtResult<T, H> {
+    /// Dew point (in °C), with the Magnus formula.
+    pub fn dew_point(&self) -> f32 {
+        const A: f32 = 17.62;
+        const B: f32 = 243.12;
 
-pub struct Dht22<P: PIOExt, STI: StateMachineIndex> {
-    dht: DhtPio<1, P, STI>,
-}
+        let gamma = self.magnus_gamma();
+        B * gamma / (A - gamma)
+    }
+
+    /// Frost point (in °C), with the Magnus formula over ice.
+    pub fn frost_point(&self) -> f32 {
+        const A: f32 = 22.46;
+        const B: f32 = 272.62;
 
-impl<P: PIOExt, STI: StateMachineIndex> Dht22<P, STI> {
-    pub fn new<I: AnyPin<Function = P::PinFunction>>(
-        pio: hal::pio::PIO<P>,
-        sm: UninitStateMachine<(P, STI)>,
-        dht_pin: I,
-        clocks: &hal::clocks::ClocksManager,
-    ) -> Self {
-        Self {
-            dht: DhtPio::new(pio, sm, dht_pin, clocks),
-        }
+        let gamma = self.magnus_gamma();
+        B * gamma / (A - gamma)
     }
 
-    #[allow(clippy::missing_errors_doc)]
-    pub fn read<D: DelayNs>(&mut self, delay: &mut D) -> Result<Dht22Result, DhtError> {
-        let (raw_temp, raw_hum) = self.dht.read_data(delay)?;
-        let mut final_t = f32::from(raw_temp & 0x7FFF);
-
-        if (raw_temp & 0x8000) > 0 {
-            final_t *= -1.0;
-        }
-
-        Ok(DhtResult {
-            temperature: final_t / 10.0,
-            humidity: f32::from(raw_hum) / 10.0,
-        })
+    /// Logarithm of the vapour pressure over the saturation pressure at 0°C.
+    fn magnus_gamma(&self) -> f32 {
+        const A: f32 = 17.62;
+        const B: f32 = 243.12;
+
+        let temperature = self.temperature.to_f32();
+        // The logarithm of 0 is not defined
+        let humidity = self.humidity.to_f32().max(0.01);
+
+        libm::logf(humidity / 100.0) + A * temperature / (B + temperature)
     }
 }
 
-pub struct Dht22Type2<P: PIOExt, STI: StateMachineIndex> {
-    dht: DhtPio<1, P, STI>,
-}
+type Dht22Result = DhtResult<f32, f32>;
+type Dht11Result = DhtResult<u16, u16>;
 
-impl<P: PIOExt, STI: StateMachineIndex> Dht22Type2<P, STI> {
-    pub fn new<I: AnyPin<Function = P::PinFunction>>(
-        pio: hal::pio::PIO<P>,
-        sm: UninitStateMachine<(P, STI)>,
-        dht_pin: I,
-        clocks: &hal::clocks::ClocksManager,
-    ) -> Self {
-        Self {
-            dht: DhtPio::new(pio, sm, dht_pin, clocks),
-        }
+/// Physical layer used by the sensors to talk to a DHT.
+///
+/// A transport only moves bits, the checksum and the decoding are done by the [`Sensor`] (see [`model`]).
+/// The transfers are provided by [`DhtTransport`] (blocking), [`NonBlockingTransport`] and `AsyncDhtTransport`.
+pub trait Transport {
+    /// Phase of the protocol in which the last transfer timed out, for the [`stats`] of the sensor.
+    fn timeout_phase(&self) -> stats::TimeoutPhase {
+        stats::TimeoutPhase::Response
     }
 
-    #[allow(clippy::missing_errors_doc, clippy::cast_possible_wrap)]
-    pub fn read<D: DelayNs>(&mut self, delay: &mut D) -> Result<Dht22Result, DhtError> {
-        let (raw_temp, raw_hum) = self.dht.read_data(delay)?;
+    /// Aborts the transfer in progress (if any), the next one starts from a clean state.
+    fn cancel(&mut self) {}
 
-        let tmp = raw_temp as i16;
+    /// Called by [`Powered`](power::Powered) when the sensor is switched on or off: while the sensor is
+    /// off, the line must not be driven high (the sensor would be powered through it).
+    fn set_sensor_power(&mut self, _powered: bool) {}
+}
 
-        Ok(DhtResult {
-            temperature: f32::from(tmp) / 10.0,
-            humidity: f32::from(raw_hum) / 10.0,
-        })
-    }
+pub trait DhtTransport: Transport {
+    /// Sends a start signal of `start_signal_length` ms and returns the 40 bits sent by the sensor
+    /// (humidity, temperature and checksum, most significant byte first).
+    #[allow(clippy::missing_errors_doc)]
+    fn transfer<D: DelayNs>(
+        &mut self,
+        start_signal_length: u32,
+        delay: &mut D,
+    ) -> Result<[u8; 5], DhtError>;
+}
+
+/// Transport receiving the frame in the background.
+pub trait NonBlockingTransport: Transport {
+    /// Sends a start signal of `start_signal_length` ms and returns immediately, the conversion
+    /// times out `timeout` after `now`. Ignored while a conversion is in progress.
+    fn start_conversion(&mut self, start_signal_length: u32, now: Instant, timeout: Duration);
+
+    /// Returns the frame of the conversion started by [`Self::start_conversion`], or `WouldBlock` if
+    /// the sensor has not answered yet. Fails with [`DhtError::Timeout`] once the sensor had the
+    /// time to answer.
+    #[allow(clippy::missing_errors_doc)]
+    fn poll(&mut self, now: Instant) -> nb::Result<[u8; 5], DhtError>;
 }
 
-pub struct Dht11<P: PIOExt, STI: StateMachineIndex> {
-    dht: DhtPio<18, P, STI>,
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncDhtTransport: Transport {
+    /// Same as [`DhtTransport::transfer`], yielding to the executor while waiting for the sensor.
+    #[allow(clippy::missing_errors_doc)]
+    async fn transfer_async<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        start_signal_length: u32,
+        delay: &mut D,
+    ) -> Result<[u8; 5], DhtError>;
 }
 
-impl<P: PIOExt, STI: StateMachineIndex> Dht11<P, STI> {
-    pub fn new<I: AnyPin<Function = P::PinFunction>>(
-        pio: hal::pio::PIO<P>,
-        sm: UninitStateMachine<(P, STI)>,
-        dht_pin: I,
-        clocks: &hal::clocks::ClocksManager,
-    ) -> Self {
-        Self {
-            dht: DhtPio::new(pio, sm, dht_pin, clocks),
-        }
+/// Instant of a [`Monotonic`] clock, with microsecond resolution.
+pub type Instant = fugit::TimerInstantU64<1_000_000>;
+/// Duration with microsecond resolution, e.g. `Duration::millis(25)`.
+pub type Duration = fugit::TimerDurationU64<1_000_000>;
+
+/// Monotonic clock used to check the read deadlines.
+pub trait Monotonic {
+    fn now(&mut self) -> Instant;
+}
+
+#[cfg(feature = "rp2040")]
+impl Monotonic for rp2040_hal::Timer {
+    fn now(&mut self) -> Instant {
+        self.get_counter()
     }
+}
 
-    #[allow(clippy::missing_errors_doc)]
-    pub fn read<D: DelayNs>(&mut self, delay: &mut D) -> Result<Dht11Result, DhtError> {
-        let (t, h) = self.dht.read_data(delay)?;
-        let mut final_t = (t & 0x7FFF) >> 8;
-
-        if (t & 0x8000) > 0 {
-            final_t = 0xFF - final_t;
-        }
-
-        Ok(DhtResult {
-            temperature: final_t,
-            humidity: h >> 8,
-        })
+#[cfg(feature = "rp235x")]
+impl<D: rp235x_hal::timer::TimerDevice> Monotonic for rp235x_hal::Timer<D> {
+    fn now(&mut self) -> Instant {
+        self.get_counter()
     }
 }
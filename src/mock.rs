@@ -0,0 +1,209 @@
+use embedded_hal::delay::DelayNs;
+
+use crate::conversion::{Conversion, Receiver};
+#[cfg(feature = "async")]
+use crate::AsyncDhtTransport;
+use crate::{encode, DhtError, DhtTransport, Duration, Instant, NonBlockingTransport, Transport};
+
+/// One step of a [`MockDht`] script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MockResponse {
+    /// Answers with the raw humidity and temperature words, the checksum is computed by the mock.
+    Data { humidity: u16, temperature: u16 },
+    /// Answers with this frame, as is.
+    Frame([u8; 5]),
+    /// Answers with the raw humidity and temperature words, but a wrong checksum.
+    CrcError { humidity: u16, temperature: u16 },
+    /// Does not answer.
+    Timeout,
+    /// Waits for the given number of milliseconds (with the delay given to `read`), then plays the next step.
+    ///
+    /// For a conversion started by `start_conversion`, delays the answer instead.
+    Delay(u32),
+}
+
+/// Scripted transport, to test the code using a sensor without any hardware.
+///
+/// Each `read` (or `start_conversion`) plays the script until a response is found, once the script
+/// is exhausted every read times out.
+///
+/// ```
+/// use dht_pio::mock::{MockDelay, MockDht, MockResponse};
+/// use dht_pio::Dht22;
+///
+/// let script = [
+///     MockResponse::Delay(2),
+///     MockResponse::Data { humidity: 652, temperature: 215 },
+///     MockResponse::Timeout,
+/// ];
+/// let mut dht = Dht22::from_transport(MockDht::new(script.into_iter()));
+/// let mut delay = MockDelay::default();
+///
+/// let result = dht.read(&mut delay).unwrap();
+/// assert_eq!(result.temperature, 21.5);
+/// assert_eq!(result.humidity, 65.2);
+/// assert!(dht.read(&mut delay).is_err());
+/// ```
+pub struct MockDht<I: Iterator<Item = MockResponse>> {
+    script: I,
+    transfers: usize,
+    last_start_signal_length: Option<u32>,
+    conversion: Option<Conversion>,
+    /// Frame of the conversion in progress, and when it is received.
+    answer: Option<(Instant, [u8; 5])>,
+}
+
+impl<I: Iterator<Item = MockResponse>> MockDht<I> {
+    pub fn new(script: I) -> Self {
+        Self {
+            script,
+            transfers: 0,
+            last_start_signal_length: None,
+            conversion: None,
+            answer: None,
+        }
+    }
+
+    /// Number of transfers requested by the sensor.
+    pub fn transfers(&self) -> usize {
+        self.transfers
+    }
+
+    /// Length (in ms) of the last start signal.
+    pub fn last_start_signal_length(&self) -> Option<u32> {
+        self.last_start_signal_length
+    }
+
+    /// Returns the remaining script.
+    pub fn free(self) -> I {
+        self.script
+    }
+
+    /// Plays the script up to the next response, returns the time (in ms) of its `Delay` steps and
+    /// the frame (`None` if the sensor does not answer).
+    fn play(&mut self, start_signal_length: u32) -> (u32, Option<[u8; 5]>) {
+        self.transfers += 1;
+        self.last_start_signal_length = Some(start_signal_length);
+
+        let mut delay_ms = 0_u32;
+        loop {
+            let frame = match self.script.next() {
+                Some(MockResponse::Data {
+                    humidity,
+                    temperature,
+                }) => encode::raw_frame(temperature, humidity),
+                Some(MockResponse::Frame(frame)) => frame,
+                Some(MockResponse::CrcError {
+                    humidity,
+                    temperature,
+                }) => {
+                    let mut frame = encode::raw_frame(temperature, humidity);
+                    frame[4] = frame[4].wrapping_add(1);
+                    frame
+                }
+                Some(MockResponse::Timeout) | None => return (delay_ms, None),
+                Some(MockResponse::Delay(ms)) => {
+                    delay_ms = delay_ms.saturating_add(ms);
+                    continue;
+                }
+            };
+            return (delay_ms, Some(frame));
+        }
+    }
+}
+
+impl<I: Iterator<Item = MockResponse>> Transport for MockDht<I> {
+    fn cancel(&mut self) {
+        self.conversion = None;
+        self.answer = None;
+    }
+}
+
+impl<I: Iterator<Item = MockResponse>> DhtTransport for MockDht<I> {
+    fn transfer<D: DelayNs>(
+        &mut self,
+        start_signal_length: u32,
+        delay: &mut D,
+    ) -> Result<[u8; 5], DhtError> {
+        let (delay_ms, frame) = self.play(start_signal_length);
+        delay.delay_ms(delay_ms);
+        frame.ok_or(DhtError::Timeout)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<I: Iterator<Item = MockResponse>> AsyncDhtTransport for MockDht<I> {
+    async fn transfer_async<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        start_signal_length: u32,
+        delay: &mut D,
+    ) -> Result<[u8; 5], DhtError> {
+        let (delay_ms, frame) = self.play(start_signal_length);
+        delay.delay_ms(delay_ms).await;
+        frame.ok_or(DhtError::Timeout)
+    }
+}
+
+/// The frame is received once the `Delay` steps before its response have elapsed.
+impl<I: Iterator<Item = MockResponse>> NonBlockingTransport for MockDht<I> {
+    fn start_conversion(&mut self, start_signal_length: u32, now: Instant, timeout: Duration) {
+        if self.conversion.is_some() {
+            return;
+        }
+
+        let (delay_ms, frame) = self.play(start_signal_length);
+        let at = now + Duration::millis(delay_ms.into());
+        self.conversion = Some(Conversion::Running(now + timeout));
+        self.answer = frame.map(|frame| (at, frame));
+    }
+
+    fn poll(&mut self, now: Instant) -> nb::Result<[u8; 5], DhtError> {
+        self.poll_conversion(now)
+    }
+}
+
+impl<I: Iterator<Item = MockResponse>> Receiver for MockDht<I> {
+    fn conversion(&mut self) -> &mut Option<Conversion> {
+        &mut self.conversion
+    }
+
+    fn frame(&mut self, now: Instant) -> nb::Result<[u8; 5], DhtError> {
+        match self.answer {
+            Some((at, frame)) if now >= at => {
+                self.answer = None;
+                Ok(frame)
+            }
+            _ => Err(nb::Error::WouldBlock),
+        }
+    }
+
+    /// No line to check, the conversion fails on the same poll.
+    fn expire(&mut self) -> Duration {
+        Duration::micros(0)
+    }
+
+    fn fail(&mut self) -> DhtError {
+        self.answer = None;
+        DhtError::Timeout
+    }
+}
+
+/// [`DelayNs`] which does not wait, but keeps track of the requested time.
+#[derive(Debug, Default)]
+pub struct MockDelay {
+    elapsed_ns: u64,
+}
+
+impl MockDelay {
+    /// Total time requested (in ns).
+    pub fn elapsed_ns(&self) -> u64 {
+        self.elapsed_ns
+    }
+}
+
+impl DelayNs for MockDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.elapsed_ns += u64::from(ns);
+    }
+}
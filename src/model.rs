@@ -0,0 +1,81 @@
+//! DHT models, the type parameter of a [`Sensor`](crate::Sensor).
+
+use crate::filter::Value;
+use crate::{decode, DhtResult};
+
+/// Start signal, warm-up time and decoding of the frames of a DHT model.
+pub trait Model {
+    type Temperature: Value;
+    type Humidity: Value;
+
+    /// Length (in ms) of the start signal.
+    const START_SIGNAL_LENGTH: u32;
+    /// Time (in ms) between power on and the first read.
+    const WARM_UP_MS: u32;
+
+    /// Decodes the raw temperature and humidity words of a frame.
+    fn decode(raw_temp: u16, raw_hum: u16) -> DhtResult<Self::Temperature, Self::Humidity>;
+
+    /// Returns `true` if the reading is in the measurement range of the sensor.
+    fn in_range(reading: &DhtResult<Self::Temperature, Self::Humidity>) -> bool;
+}
+
+/// DHT22 (AM2302), sign and magnitude temperature.
+pub struct Dht22;
+
+impl Model for Dht22 {
+    type Temperature = f32;
+    type Humidity = f32;
+
+    const START_SIGNAL_LENGTH: u32 = 1;
+    const WARM_UP_MS: u32 = 2000;
+
+    fn decode(raw_temp: u16, raw_hum: u16) -> DhtResult<f32, f32> {
+        decode::dht22(raw_temp, raw_hum)
+    }
+
+    /// -40°C to 80°C, 0% to 100%.
+    fn in_range(reading: &DhtResult<f32, f32>) -> bool {
+        decode::dht22_in_range(reading)
+    }
+}
+
+/// DHT22 sending the temperature in two's complement, see the readme.
+pub struct Dht22Type2;
+
+impl Model for Dht22Type2 {
+    type Temperature = f32;
+    type Humidity = f32;
+
+    const START_SIGNAL_LENGTH: u32 = 1;
+    const WARM_UP_MS: u32 = 2000;
+
+    fn decode(raw_temp: u16, raw_hum: u16) -> DhtResult<f32, f32> {
+        decode::dht22_type2(raw_temp, raw_hum)
+    }
+
+    /// -40°C to 80°C, 0% to 100%.
+    fn in_range(reading: &DhtResult<f32, f32>) -> bool {
+        decode::dht22_in_range(reading)
+    }
+}
+
+/// DHT11, integer values.
+pub struct Dht11;
+
+impl Model for Dht11 {
+    type Temperature = u16;
+    type Humidity = u16;
+
+    const START_SIGNAL_LENGTH: u32 = 18;
+    const WARM_UP_MS: u32 = 1000;
+
+    fn decode(raw_temp: u16, raw_hum: u16) -> DhtResult<u16, u16> {
+        decode::dht11(raw_temp, raw_hum)
+    }
+
+    /// -20°C to 60°C, 0% to 100%.
+    fn in_range(reading: &DhtResult<u16, u16>) -> bool {
+        decode::dht11_in_range(reading)
+    }
+}
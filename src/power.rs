@@ -0,0 +1,258 @@
+//! Power management of the sensor through a GPIO.
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+use crate::stats::TimeoutPhase;
+#[cfg(feature = "async")]
+use crate::AsyncDhtTransport;
+use crate::{DhtError, DhtTransport, Duration, Instant, NonBlockingTransport, Transport};
+
+/// Time the sensor stays off when it is power-cycled (in ms).
+const POWER_CYCLE_OFF_MS: u32 = 1000;
+
+/// Transport powering the sensor through a pin (any [`OutputPin`], high when the sensor is powered).
+///
+/// The sensor is powered on before the first read, which first waits for the warm-up time of the
+/// sensor (for example [`Sensor::WARM_UP_MS`](crate::Sensor::WARM_UP_MS)).
+///
+/// While the sensor is off, the transport drives the data line low (see [`Transport::set_sensor_power`]),
+/// so that the sensor is not powered through it.
+pub struct Powered<T, P: OutputPin> {
+    transport: T,
+    power: P,
+    warm_up_ms: u32,
+    low_power: bool,
+    power_cycle_after: u32,
+    consecutive_failures: u32,
+    powered: bool,
+    cycle: Option<PowerCycle>,
+    pending: Option<Pending>,
+}
+
+/// Power off of a power cycle, the sensor stays off for [`POWER_CYCLE_OFF_MS`] before the next power on.
+#[derive(Clone, Copy)]
+enum PowerCycle {
+    /// By a blocking read, the whole off time is waited before the next power on.
+    Untimed,
+    /// By a non-blocking read, at this instant.
+    At(Instant),
+}
+
+/// Conversion started while the sensor is off, sent once it is powered on and warmed up.
+#[derive(Clone, Copy)]
+struct Pending {
+    start_signal_length: u32,
+    timeout: Duration,
+    /// Instant of the next step: the power on, then the start signal.
+    at: Instant,
+}
+
+impl<T: Transport, P: OutputPin> Powered<T, P> {
+    /// The sensor is powered off until the first read.
+    pub fn new(mut transport: T, mut power: P, warm_up_ms: u32) -> Self {
+        power.set_low().ok();
+        transport.set_sensor_power(false);
+
+        Self {
+            transport,
+            power,
+            warm_up_ms,
+            low_power: false,
+            power_cycle_after: 0,
+            consecutive_failures: 0,
+            powered: false,
+            cycle: None,
+            pending: None,
+        }
+    }
+
+    /// Powers the sensor off after each read (and on again, with its warm-up, before the next one).
+    #[must_use]
+    pub fn low_power(mut self, low_power: bool) -> Self {
+        self.low_power = low_power;
+        self
+    }
+
+    /// Power-cycles the sensor after `failures` consecutive reads without answer (0 disables it).
+    ///
+    /// The sensor is kept off for 1s before it is powered on again (with its warm-up) by the next read.
+    #[must_use]
+    pub fn power_cycle_after(mut self, failures: u32) -> Self {
+        self.power_cycle_after = failures;
+        self
+    }
+
+    pub fn is_powered(&self) -> bool {
+        self.powered
+    }
+
+    /// Number of reads without answer since the last successful one.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// Powers the sensor off, it is powered on again on the next read.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn power_off(&mut self) -> Result<(), DhtError> {
+        self.power.set_low().map_err(|_| DhtError::PinError)?;
+        self.powered = false;
+        self.transport.set_sensor_power(false);
+        Ok(())
+    }
+
+    /// Returns the transport and the power pin.
+    pub fn free(self) -> (T, P) {
+        (self.transport, self.power)
+    }
+
+    fn power_on(&mut self) -> Result<(), DhtError> {
+        self.power.set_high().map_err(|_| DhtError::PinError)?;
+        self.powered = true;
+        self.transport.set_sensor_power(true);
+        Ok(())
+    }
+
+    /// Time (in ms) the sensor must still stay off before a blocking power on.
+    fn off_time_left(&mut self) -> u32 {
+        self.cycle.take().map_or(0, |_| POWER_CYCLE_OFF_MS)
+    }
+
+    /// Counts the failures and powers the sensor off after a read, `now` is known for non-blocking reads.
+    fn complete<R>(
+        &mut self,
+        result: &Result<R, DhtError>,
+        now: Option<Instant>,
+    ) -> Result<(), DhtError> {
+        if result.is_ok() {
+            self.consecutive_failures = 0;
+        } else {
+            self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        }
+
+        if self.power_cycle_after > 0 && self.consecutive_failures >= self.power_cycle_after {
+            self.power_off()?;
+            self.cycle = Some(now.map_or(PowerCycle::Untimed, PowerCycle::At));
+            self.consecutive_failures = 0;
+        } else if self.low_power {
+            self.power_off()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: DhtTransport, P: OutputPin> DhtTransport for Powered<T, P> {
+    fn transfer<D: DelayNs>(
+        &mut self,
+        start_signal_length: u32,
+        delay: &mut D,
+    ) -> Result<[u8; 5], DhtError> {
+        if !self.powered {
+            delay.delay_ms(self.off_time_left());
+            self.power_on()?;
+            delay.delay_ms(self.warm_up_ms);
+        }
+
+        let result = self.transport.transfer(start_signal_length, delay);
+        self.complete(&result, None)?;
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncDhtTransport, P: OutputPin> AsyncDhtTransport for Powered<T, P> {
+    async fn transfer_async<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        start_signal_length: u32,
+        delay: &mut D,
+    ) -> Result<[u8; 5], DhtError> {
+        if !self.powered {
+            delay.delay_ms(self.off_time_left()).await;
+            self.power_on()?;
+            delay.delay_ms(self.warm_up_ms).await;
+        }
+
+        let result = self
+            .transport
+            .transfer_async(start_signal_length, delay)
+            .await;
+        self.complete(&result, None)?;
+        result
+    }
+}
+
+/// The timeout of a conversion starting while the sensor is off runs from the start signal, sent
+/// by a later poll once the sensor is powered on and warmed up.
+impl<T: NonBlockingTransport, P: OutputPin> NonBlockingTransport for Powered<T, P> {
+    fn start_conversion(&mut self, start_signal_length: u32, now: Instant, timeout: Duration) {
+        if self.pending.is_some() {
+            return;
+        }
+
+        if self.powered {
+            self.transport
+                .start_conversion(start_signal_length, now, timeout);
+            return;
+        }
+
+        let off = Duration::millis(POWER_CYCLE_OFF_MS.into());
+        let at = match self.cycle.take() {
+            Some(PowerCycle::At(instant)) => instant + off,
+            Some(PowerCycle::Untimed) => now + off,
+            None => now,
+        };
+        self.pending = Some(Pending {
+            start_signal_length,
+            timeout,
+            at,
+        });
+    }
+
+    fn poll(&mut self, now: Instant) -> nb::Result<[u8; 5], DhtError> {
+        if let Some(pending) = self.pending {
+            if now < pending.at {
+                return Err(nb::Error::WouldBlock);
+            }
+
+            if self.powered {
+                self.pending = None;
+                self.transport
+                    .start_conversion(pending.start_signal_length, now, pending.timeout);
+            } else {
+                self.pending = None;
+                self.power_on()?;
+                self.pending = Some(Pending {
+                    at: now + Duration::millis(self.warm_up_ms.into()),
+                    ..pending
+                });
+            }
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let result = match self.transport.poll(now) {
+            Err(nb::Error::WouldBlock) => return Err(nb::Error::WouldBlock),
+            Err(nb::Error::Other(e)) => Err(e),
+            Ok(frame) => Ok(frame),
+        };
+        self.complete(&result, Some(now))?;
+        Ok(result?)
+    }
+}
+
+impl<T: Transport, P: OutputPin> Transport for Powered<T, P> {
+    fn timeout_phase(&self) -> TimeoutPhase {
+        self.transport.timeout_phase()
+    }
+
+    /// A conversion waiting for the warm-up of the sensor is dropped, and the sensor powered off.
+    fn cancel(&mut self) {
+        self.transport.cancel();
+        if self.pending.take().is_some() && self.powered {
+            self.power_off().ok();
+        } else if !self.powered {
+            // The cancelled transport drives the line high again
+            self.transport.set_sensor_power(false);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use core::marker::PhantomData;
+
+use embedded_hal::delay::DelayNs;
+
+use crate::calibration::Calibration;
+use crate::model::{self, Model};
+use crate::stats::{Stats, TimeoutPhase};
+#[cfg(feature = "async")]
+use crate::AsyncDhtTransport;
+use crate::{decode, DhtError, DhtResult, DhtTransport, Duration, Instant, Monotonic};
+use crate::{NonBlockingTransport, Transport};
+
+/// Time (in ms) left to the sensor to send its frame after the start signal.
+pub(crate) const ANSWER_TIMEOUT: u32 = 10;
+
+/// Reading of a model.
+pub(crate) type Reading<M> = DhtResult<<M as Model>::Temperature, <M as Model>::Humidity>;
+
+/// DHT sensor of model `M` (see [`model`]), reached through the transport `T`.
+pub struct Sensor<M: Model, T> {
+    pub(crate) transport: T,
+    stats: Stats,
+    check_range: bool,
+    calibration: Calibration,
+    uncalibrated: Option<Reading<M>>,
+    model: PhantomData<M>,
+}
+
+pub type Dht22<T> = Sensor<model::Dht22, T>;
+pub type Dht22Type2<T> = Sensor<model::Dht22Type2, T>;
+pub type Dht11<T> = Sensor<model::Dht11, T>;
+
+impl<M: Model, T> Sensor<M, T> {
+    /// Time (in ms) between power on and the first read.
+    pub const WARM_UP_MS: u32 = M::WARM_UP_MS;
+
+    pub fn from_transport(transport: T) -> Self {
+        Self {
+            transport,
+            stats: Stats::default(),
+            check_range: false,
+            calibration: Calibration::default(),
+            uncalibrated: None,
+            model: PhantomData,
+        }
+    }
+
+    /// Returns the transport.
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    /// Rejects the readings out of the range of the sensor (see [`Model::in_range`]) with [`DhtError::OutOfRange`].
+    #[must_use]
+    pub fn check_range(mut self, enabled: bool) -> Self {
+        self.check_range = enabled;
+        self
+    }
+
+    /// Snapshot of the counters since the creation of the sensor (or the last [`Self::reset_stats`]).
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::default();
+    }
+
+    /// Calibration applied to the readings (none by default).
+    #[must_use]
+    pub fn calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = calibration;
+        self
+    }
+
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = calibration;
+    }
+
+    /// Reading of the last successful read, before calibration.
+    pub fn last_uncalibrated(&self) -> Option<Reading<M>> {
+        self.uncalibrated
+    }
+
+    /// Decodes the frame received by the transport, calibrates the reading and updates the counters.
+    pub(crate) fn complete(
+        &mut self,
+        frame: Result<[u8; 5], DhtError>,
+        timeout_phase: TimeoutPhase,
+        now: Option<Instant>,
+    ) -> Result<Reading<M>, DhtError> {
+        let result = frame.and_then(|frame| self.decode(frame));
+        self.stats.record(&result, timeout_phase, now);
+
+        let reading = result?;
+        self.uncalibrated = Some(reading);
+        Ok(self.calibration.apply(reading))
+    }
+
+    fn decode(&self, frame: [u8; 5]) -> Result<Reading<M>, DhtError> {
+        let (raw_temp, raw_hum) = decode::check_frame(frame)?;
+        let reading = M::decode(raw_temp, raw_hum);
+
+        if self.check_range && !M::in_range(&reading) {
+            return Err(DhtError::OutOfRange);
+        }
+
+        Ok(reading)
+    }
+}
+
+impl<M: Model, T: Transport> Sensor<M, T> {
+    /// Aborts the conversion in progress (if any, e.g. after dropping the future of a `read_async`),
+    /// the next read starts from a clean state.
+    pub fn cancel(&mut self) {
+        self.transport.cancel();
+    }
+}
+
+impl<M: Model, T: DhtTransport> Sensor<M, T> {
+    #[allow(clippy::missing_errors_doc)]
+    pub fn read<D: DelayNs>(&mut self, delay: &mut D) -> Result<Reading<M>, DhtError> {
+        let frame = self.transport.transfer(M::START_SIGNAL_LENGTH, delay);
+        self.complete(frame, self.transport.timeout_phase(), None)
+    }
+}
+
+impl<M: Model, T: NonBlockingTransport> Sensor<M, T> {
+    /// Sends the start signal to the sensor and returns immediately, use [`Self::poll`] to get the result.
+    ///
+    /// `now` (e.g. from a [`Monotonic`](crate::Monotonic) clock) starts the timeout of the conversion,
+    /// nothing is sent while a conversion is in progress.
+    pub fn start_conversion(&mut self, now: Instant) {
+        let timeout = Duration::millis((M::START_SIGNAL_LENGTH + ANSWER_TIMEOUT).into());
+        self.transport
+            .start_conversion(M::START_SIGNAL_LENGTH, now, timeout);
+    }
+
+    /// Returns the result of the conversion started by [`Self::start_conversion`], or `WouldBlock` if the sensor has not answered yet.
+    ///
+    /// Fails with [`DhtError::Timeout`] (and gets ready for the next conversion) if the sensor does not answer in time.
+    pub fn poll(&mut self, now: Instant) -> nb::Result<Reading<M>, DhtError> {
+        let frame = match self.transport.poll(now) {
+            Err(nb::Error::WouldBlock) => return Err(nb::Error::WouldBlock),
+            Err(nb::Error::Other(e)) => Err(e),
+            Ok(frame) => Ok(frame),
+        };
+        Ok(self.complete(frame, self.transport.timeout_phase(), Some(now))?)
+    }
+
+    /// Same as [`Self::read`], but gives up `timeout` after the beginning of the start signal,
+    /// and returns as soon as the frame is received.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn read_with_timeout<C: Monotonic>(
+        &mut self,
+        clock: &mut C,
+        timeout: Duration,
+    ) -> Result<Reading<M>, DhtError> {
+        self.transport
+            .start_conversion(M::START_SIGNAL_LENGTH, clock.now(), timeout);
+
+        loop {
+            match self.poll(clock.now()) {
+                Err(nb::Error::WouldBlock) => (),
+                Err(nb::Error::Other(e)) => return Err(e),
+                Ok(reading) => return Ok(reading),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<M: Model, T: AsyncDhtTransport> Sensor<M, T> {
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn read_async<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Reading<M>, DhtError> {
+        let frame = self
+            .transport
+            .transfer_async(M::START_SIGNAL_LENGTH, delay)
+            .await;
+        self.complete(frame, self.transport.timeout_phase(), None)
+    }
+}
@@ -0,0 +1,81 @@
+//! Reliability counters of a sensor, see [`Sensor::stats`](crate::Sensor::stats).
+
+use crate::{DhtError, Instant};
+
+/// Phase of the protocol in which a transfer timed out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeoutPhase {
+    /// The sensor did not answer the start signal.
+    Response,
+    /// The sensor stopped in the middle of the frame.
+    Data,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Stats {
+    pub successes: u32,
+    /// Timeouts without answer of the sensor.
+    pub response_timeouts: u32,
+    /// Timeouts in the middle of the frame.
+    pub data_timeouts: u32,
+    pub checksum_failures: u32,
+    /// Readings rejected by the range check (see [`Sensor::check_range`](crate::Sensor::check_range)).
+    pub range_rejections: u32,
+    /// Other errors (pin errors, stuck line...).
+    pub other_failures: u32,
+    /// Reads started after a failed one.
+    pub retries: u32,
+    pub consecutive_failures: u32,
+    pub last_error: Option<DhtError>,
+    /// Time of the last error, only known for the reads made with a clock
+    /// (e.g. [`Sensor::read_with_timeout`](crate::Sensor::read_with_timeout)).
+    pub last_error_at: Option<Instant>,
+}
+
+impl Stats {
+    /// Total number of failed reads.
+    pub fn failures(&self) -> u32 {
+        self.response_timeouts
+            + self.data_timeouts
+            + self.checksum_failures
+            + self.range_rejections
+            + self.other_failures
+    }
+
+    pub(crate) fn record<R>(
+        &mut self,
+        result: &Result<R, DhtError>,
+        timeout_phase: TimeoutPhase,
+        now: Option<Instant>,
+    ) {
+        if self.consecutive_failures > 0 {
+            self.retries = self.retries.saturating_add(1);
+        }
+
+        let error = match result {
+            Ok(_) => {
+                self.successes = self.successes.saturating_add(1);
+                self.consecutive_failures = 0;
+                return;
+            }
+            Err(e) => *e,
+        };
+
+        let counter = match error {
+            DhtError::Timeout => match timeout_phase {
+                TimeoutPhase::Response => &mut self.response_timeouts,
+                TimeoutPhase::Data => &mut self.data_timeouts,
+            },
+            DhtError::CrcMismatch(_, _) => &mut self.checksum_failures,
+            DhtError::OutOfRange => &mut self.range_rejections,
+            _ => &mut self.other_failures,
+        };
+        *counter = counter.saturating_add(1);
+
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.last_error = Some(error);
+        self.last_error_at = now;
+    }
+}
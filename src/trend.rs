@@ -0,0 +1,166 @@
+//! Rate of change of the readings of a [`History`], by least squares.
+
+use crate::filter::Value;
+use crate::history::{History, Sample};
+use crate::{DhtResult, Duration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    Rising,
+    Falling,
+    Stable,
+}
+
+/// Rate of change of a value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Rate {
+    /// Slope, in unit (°C or %RH) per hour.
+    pub per_hour: f32,
+    pub direction: Direction,
+    /// The value changes faster than the rapid change limit (door opening, humidifier failing...).
+    pub rapid: bool,
+}
+
+/// Limits of a [`Rate`], in unit per hour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Limits {
+    /// Stable below this rate.
+    pub stable: f32,
+    /// Rapid change above this rate.
+    pub rapid: f32,
+}
+
+impl Limits {
+    fn rate(self, per_hour: f32) -> Rate {
+        let direction = if per_hour > self.stable {
+            Direction::Rising
+        } else if per_hour < -self.stable {
+            Direction::Falling
+        } else {
+            Direction::Stable
+        };
+
+        Rate {
+            per_hour,
+            direction,
+            rapid: libm::fabsf(per_hour) > self.rapid,
+        }
+    }
+}
+
+/// Trend analysis of the readings.
+///
+/// ```ignore
+/// let trend = Trend::new().window(Duration::minutes(15));
+/// if let Some(rates) = trend.analyze(&history) {
+///     if rates.humidity.rapid {
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Trend {
+    temperature: Limits,
+    humidity: Limits,
+    window: Option<Duration>,
+}
+
+impl Default for Trend {
+    fn default() -> Self {
+        Self {
+            temperature: Limits {
+                stable: 0.5,
+                rapid: 5.0,
+            },
+            humidity: Limits {
+                stable: 2.0,
+                rapid: 20.0,
+            },
+            window: None,
+        }
+    }
+}
+
+impl Trend {
+    /// Stable below 0.5°C/h and 2%RH/h, rapid change above 5°C/h and 20%RH/h, on the whole history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn temperature(mut self, limits: Limits) -> Self {
+        self.temperature = limits;
+        self
+    }
+
+    #[must_use]
+    pub fn humidity(mut self, limits: Limits) -> Self {
+        self.humidity = limits;
+        self
+    }
+
+    /// Only the samples of the last `window` before the latest one are used.
+    #[must_use]
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    /// Rates of change of the history, `None` without at least two samples at different times.
+    pub fn analyze<const N: usize, T: Value, H: Value>(
+        &self,
+        history: &History<N, T, H>,
+    ) -> Option<DhtResult<Rate, Rate>> {
+        let latest = history.latest()?.at;
+        let since = self
+            .window
+            .and_then(|window| latest.checked_sub_duration(window));
+        let samples = history
+            .iter()
+            .filter(move |s| since.is_none_or(|since| s.at >= since));
+
+        Some(DhtResult {
+            temperature: self
+                .temperature
+                .rate(slope(samples.clone(), |s| s.reading.temperature.to_f32())?),
+            humidity: self
+                .humidity
+                .rate(slope(samples, |s| s.reading.humidity.to_f32())?),
+        })
+    }
+}
+
+/// Least-squares slope (per hour) of the values of the samples.
+#[allow(clippy::cast_precision_loss)]
+fn slope<'a, T: Value + 'a, H: Value + 'a>(
+    samples: impl Iterator<Item = &'a Sample<T, H>> + Clone,
+    value: impl Fn(&Sample<T, H>) -> f32,
+) -> Option<f32> {
+    let origin = samples.clone().next()?.at;
+    // Hours since the first sample, small enough to keep the precision of f32
+    let hours = |s: &Sample<T, H>| {
+        s.at.checked_duration_since(origin)
+            .map_or(0.0, |d| d.ticks() as f32 / 3_600_000_000.0)
+    };
+
+    let mut count = 0_u32;
+    let (mut sum_x, mut sum_y) = (0.0, 0.0);
+    for sample in samples.clone() {
+        count += 1;
+        sum_x += hours(sample);
+        sum_y += value(sample);
+    }
+    let (mean_x, mean_y) = (sum_x / count as f32, sum_y / count as f32);
+
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for sample in samples {
+        let dx = hours(sample) - mean_x;
+        covariance += dx * (value(sample) - mean_y);
+        variance += dx * dx;
+    }
+
+    (variance > 0.0).then(|| covariance / variance)
+}
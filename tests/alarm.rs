@@ -0,0 +1,74 @@
+mod common;
+
+use common::{at, reading};
+use dht_pio::alarm::{AlarmEvent, AlarmKind, Alarms, Threshold};
+use dht_pio::{DhtResult, Duration};
+
+fn events(alarms: &mut Alarms, reading: Option<DhtResult<f32, f32>>, secs: u64) -> Vec<AlarmEvent> {
+    let mut events = Vec::new();
+    alarms.update(reading.as_ref(), at(secs), |event| events.push(event));
+    events
+}
+
+#[test]
+fn hysteresis() {
+    let mut alarms = Alarms::new().over_temperature(Threshold::new(30.0).hysteresis(1.0));
+
+    assert!(events(&mut alarms, Some(reading(29.0, 50.0)), 0).is_empty());
+    assert_eq!(
+        events(&mut alarms, Some(reading(30.5, 50.0)), 1),
+        [AlarmEvent::Raised(AlarmKind::OverTemperature)]
+    );
+    // Within the hysteresis
+    assert!(events(&mut alarms, Some(reading(29.5, 50.0)), 2).is_empty());
+    assert!(alarms.is_active(AlarmKind::OverTemperature));
+    assert_eq!(
+        events(&mut alarms, Some(reading(28.5, 50.0)), 3),
+        [AlarmEvent::Cleared(AlarmKind::OverTemperature)]
+    );
+}
+
+#[test]
+fn hold_time() {
+    let mut alarms = Alarms::new().under_humidity(Threshold::new(30.0).hold(Duration::secs(10)));
+
+    assert!(events(&mut alarms, Some(reading(20.0, 25.0)), 0).is_empty());
+    // Back to normal before the hold time: the pending change is dropped
+    assert!(events(&mut alarms, Some(reading(20.0, 35.0)), 5).is_empty());
+    assert!(events(&mut alarms, Some(reading(20.0, 25.0)), 6).is_empty());
+    assert!(events(&mut alarms, Some(reading(20.0, 25.0)), 12).is_empty());
+    assert_eq!(
+        events(&mut alarms, Some(reading(20.0, 25.0)), 16),
+        [AlarmEvent::Raised(AlarmKind::UnderHumidity)]
+    );
+}
+
+#[test]
+fn dew_point() {
+    assert!((reading(20.0, 50.0).dew_point() - 9.3).abs() < 0.1);
+
+    let mut alarms = Alarms::new().dew_point(Threshold::new(2.0));
+
+    assert!(events(&mut alarms, Some(reading(20.0, 50.0)), 0).is_empty());
+    assert_eq!(
+        events(&mut alarms, Some(reading(20.0, 95.0)), 1),
+        [AlarmEvent::Raised(AlarmKind::DewPoint)]
+    );
+}
+
+#[test]
+fn offline() {
+    let mut alarms = Alarms::new().offline_after(Duration::secs(30));
+
+    assert!(events(&mut alarms, Some(reading(20.0, 50.0)), 0).is_empty());
+    assert!(events(&mut alarms, None, 20).is_empty());
+    assert_eq!(
+        events(&mut alarms, None, 30),
+        [AlarmEvent::Raised(AlarmKind::Offline)]
+    );
+    assert!(events(&mut alarms, None, 40).is_empty());
+    assert_eq!(
+        events(&mut alarms, Some(reading(20.0, 50.0)), 50),
+        [AlarmEvent::Cleared(AlarmKind::Offline)]
+    );
+}
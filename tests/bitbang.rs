@@ -0,0 +1,95 @@
+use dht_pio::bitbang::DhtBitBang;
+use dht_pio::stats::TimeoutPhase;
+use dht_pio::{encode, DhtError, DhtTransport, Transport};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+use embedded_hal_mock::eh1::digital::{Mock, State, Transaction};
+
+/// Pin reads of a pulse lasting `polls` polls, ended by the read of the next level.
+fn pulse(level: State, polls: usize) -> Vec<Transaction> {
+    let next = match level {
+        State::High => State::Low,
+        State::Low => State::High,
+    };
+    let mut transactions = vec![Transaction::get(level); polls];
+    transactions.push(Transaction::get(next));
+    transactions
+}
+
+/// Pin transactions up to the release of the line at the end of the start signal.
+fn start_signal() -> Vec<Transaction> {
+    vec![
+        // Released by `new`
+        Transaction::set(State::High),
+        // Idle high
+        Transaction::get(State::High),
+        Transaction::set(State::Low),
+        Transaction::get(State::Low),
+        Transaction::set(State::High),
+    ]
+}
+
+fn transfer(transactions: &[Transaction]) -> (Result<[u8; 5], DhtError>, TimeoutPhase) {
+    let mut pin = Mock::new(transactions);
+    let mut dht = DhtBitBang::new(pin.clone());
+
+    let result = dht.transfer(1, &mut NoopDelay::new());
+    let timeout_phase = dht.timeout_phase();
+
+    drop(dht);
+    pin.done();
+    (result, timeout_phase)
+}
+
+#[test]
+fn valid_frame() {
+    let frame = encode::raw_frame(215, 652);
+
+    let mut transactions = start_signal();
+    // Sensor pulling the line low after ~20µs, then its ACK
+    transactions.extend(pulse(State::High, 2));
+    transactions.extend(pulse(State::Low, 80));
+    transactions.extend(pulse(State::High, 80));
+    for byte in frame {
+        for bit in (0..8).rev() {
+            transactions.extend(pulse(State::Low, 50));
+            let high = if byte & (1 << bit) != 0 { 70 } else { 27 };
+            transactions.extend(pulse(State::High, high));
+        }
+    }
+
+    assert_eq!(transfer(&transactions).0, Ok(frame));
+}
+
+#[test]
+fn missing_ack() {
+    let mut transactions = start_signal();
+    // The line stays high after the start signal, until the pulse limit
+    transactions.extend(vec![Transaction::get(State::High); 102]);
+    transactions.push(Transaction::set(State::High));
+
+    let (result, timeout_phase) = transfer(&transactions);
+
+    assert_eq!(result, Err(DhtError::Timeout));
+    assert_eq!(timeout_phase, TimeoutPhase::Response);
+}
+
+#[test]
+fn line_stuck_low() {
+    let transactions = [Transaction::set(State::High), Transaction::get(State::Low)];
+
+    assert_eq!(transfer(&transactions).0, Err(DhtError::LineStuckLow));
+}
+
+#[test]
+fn line_stuck_high() {
+    let transactions = [
+        Transaction::set(State::High),
+        Transaction::get(State::High),
+        Transaction::set(State::Low),
+        // Still high while the start signal drives it low
+        Transaction::get(State::High),
+        Transaction::set(State::High),
+    ];
+
+    assert_eq!(transfer(&transactions).0, Err(DhtError::LineStuckHigh));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::answering;
+use dht_pio::calibration::{Calibration, Correction};
+use dht_pio::mock::MockDelay;
+use dht_pio::{encode, Dht11, Dht22, DhtResult};
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+}
+
+#[test]
+fn offset_and_gain() {
+    assert_close(Correction::offset(-1.5).apply(20.0), 18.5);
+    assert_close(Correction::linear(1.1, 2.0).apply(10.0), 13.0);
+    assert_eq!(Correction::IDENTITY.apply(21.3), 21.3);
+}
+
+#[test]
+fn two_point() {
+    // Salt references: 75.3%RH read as 78%, 11.3%RH read as 15%
+    let correction = Correction::two_point((15.0, 11.3), (78.0, 75.3)).unwrap();
+
+    assert_close(correction.apply(15.0), 11.3);
+    assert_close(correction.apply(78.0), 75.3);
+    assert_close(correction.apply(46.5), 43.3);
+
+    assert_eq!(Correction::two_point((20.0, 19.0), (20.0, 25.0)), None);
+}
+
+#[test]
+fn humidity_is_clamped() {
+    let calibration = Calibration::new(Correction::IDENTITY, Correction::offset(5.0));
+    let reading = calibration.apply(DhtResult {
+        temperature: 20.0_f32,
+        humidity: 98.0_f32,
+    });
+
+    assert_eq!(reading.humidity, 100.0);
+}
+
+#[test]
+fn integer_readings_are_rounded() {
+    let calibration = Calibration::new(Correction::offset(-1.4), Correction::linear(1.1, 0.0));
+    let reading = calibration.apply(DhtResult {
+        temperature: 23_u16,
+        humidity: 50_u16,
+    });
+
+    assert_eq!(reading.temperature, 22);
+    assert_eq!(reading.humidity, 55);
+}
+
+#[test]
+fn bytes_round_trip() {
+    let calibration = Calibration::new(
+        Correction::linear(1.02, -0.8),
+        Correction::two_point((15.0, 11.3), (78.0, 75.3)).unwrap(),
+    );
+
+    let bytes = calibration.to_bytes();
+    assert_eq!(bytes.len(), Calibration::SIZE);
+    assert_eq!(Calibration::from_bytes(&bytes), Some(calibration));
+}
+
+#[test]
+fn invalid_bytes() {
+    let mut bytes = Calibration::default().to_bytes();
+    bytes[3] ^= 0x10;
+
+    assert_eq!(Calibration::from_bytes(&bytes), None);
+    assert_eq!(Calibration::from_bytes(&[0xFF; Calibration::SIZE]), None);
+    assert_eq!(Calibration::from_bytes(&bytes[..10]), None);
+}
+
+#[test]
+fn applied_by_the_sensor() {
+    let raw = DhtResult {
+        temperature: 21.5,
+        humidity: 65.2,
+    };
+    let mut dht = Dht22::from_transport(answering(encode::dht22(&raw))).calibration(
+        Calibration::new(Correction::offset(-1.0), Correction::offset(2.0)),
+    );
+
+    let reading = dht.read(&mut MockDelay::default()).unwrap();
+    assert_close(reading.temperature, 20.5);
+    assert_close(reading.humidity, 67.2);
+    assert_eq!(dht.last_uncalibrated(), Some(raw));
+
+    let mut dht11 = Dht11::from_transport(answering(encode::dht11(&DhtResult {
+        temperature: 23,
+        humidity: 41,
+    })))
+    .calibration(Calibration::new(
+        Correction::offset(1.0),
+        Correction::IDENTITY,
+    ));
+    assert_eq!(
+        dht11.read(&mut MockDelay::default()).unwrap().temperature,
+        24
+    );
+}
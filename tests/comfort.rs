@@ -0,0 +1,80 @@
+mod common;
+
+use common::reading;
+use dht_pio::comfort::{humidex, Comfort, HumidexBand, Label, Zone};
+use dht_pio::{DhtResult, Instant};
+
+fn hours(hours: u64) -> Instant {
+    Instant::from_ticks(hours * 3600 * 1_000_000)
+}
+
+#[test]
+fn ashrae_zone() {
+    assert_eq!(Zone::classify(&reading(22.0, 45.0)), Zone::Comfortable);
+    assert_eq!(Zone::classify(&reading(18.0, 45.0)), Zone::Cold);
+    assert_eq!(Zone::classify(&reading(28.0, 45.0)), Zone::Hot);
+    assert_eq!(Zone::classify(&reading(22.0, 20.0)), Zone::Dry);
+    assert_eq!(Zone::classify(&reading(22.0, 65.0)), Zone::Humid);
+    // Dew point above 16.8°C
+    assert_eq!(Zone::classify(&reading(26.0, 59.0)), Zone::Humid);
+    // Fixed-point readings
+    let dht11 = DhtResult::<u16, u16> {
+        temperature: 22,
+        humidity: 45,
+    };
+    assert_eq!(Zone::classify(&dht11), Zone::Comfortable);
+}
+
+#[test]
+fn humidex_bands() {
+    // 30°C and a dew point of 15°C: humidex of 34
+    let h = humidex(&reading(30.0, 40.0));
+    assert!((h - 34.0).abs() < 1.0);
+
+    assert_eq!(
+        HumidexBand::classify(&reading(22.0, 45.0)),
+        HumidexBand::Comfortable
+    );
+    assert_eq!(
+        HumidexBand::classify(&reading(30.0, 40.0)),
+        HumidexBand::SomeDiscomfort
+    );
+    assert_eq!(
+        HumidexBand::classify(&reading(35.0, 70.0)),
+        HumidexBand::Dangerous
+    );
+    assert_eq!(
+        HumidexBand::classify(&reading(38.0, 80.0)),
+        HumidexBand::HeatStroke
+    );
+}
+
+#[test]
+fn mould_risk() {
+    let mut comfort = Comfort::new();
+
+    assert_eq!(
+        comfort.update(&reading(18.0, 20.0), hours(0)).label,
+        Label::Dry
+    );
+    assert_eq!(
+        comfort.update(&reading(18.0, 75.0), hours(1)).label,
+        Label::Humid
+    );
+    assert_eq!(
+        comfort.update(&reading(18.0, 75.0), hours(12)).label,
+        Label::Humid
+    );
+    assert_eq!(
+        comfort.update(&reading(18.0, 75.0), hours(13)).label,
+        Label::MouldRisk
+    );
+
+    // Dried out
+    assert_eq!(
+        comfort.update(&reading(18.0, 50.0), hours(14)).label,
+        Label::Comfortable
+    );
+    assert!(!comfort.is_mould_risk(hours(14)));
+    assert_eq!(Label::MouldRisk as u8, 3);
+}
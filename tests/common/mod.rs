@@ -0,0 +1,73 @@
+#![allow(dead_code)]
+
+pub mod pio_sim;
+pub mod sensor;
+pub mod waveform;
+
+use core::fmt::Debug;
+
+use dht_pio::model::{self, Model};
+use dht_pio::{encode, DhtResult, DhtTransport, Instant, Sensor};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+pub const DHT22_START_SIGNAL: u32 = model::Dht22::START_SIGNAL_LENGTH;
+pub const DHT11_START_SIGNAL: u32 = model::Dht11::START_SIGNAL_LENGTH;
+
+/// 65.2 %RH, 21.5 °C
+pub const FRAME: [u8; 5] = [0x02, 0x8C, 0x00, 0xD7, 0x65];
+
+/// Instant `secs` seconds after the start of the clock.
+pub fn at(secs: u64) -> Instant {
+    Instant::from_ticks(secs * 1_000_000)
+}
+
+pub fn reading<T, H>(temperature: T, humidity: H) -> DhtResult<T, H> {
+    DhtResult {
+        temperature,
+        humidity,
+    }
+}
+
+/// Transport answering once with `frame`.
+#[cfg(feature = "mock")]
+pub fn answering(
+    frame: [u8; 5],
+) -> dht_pio::mock::MockDht<core::iter::Once<dht_pio::mock::MockResponse>> {
+    dht_pio::mock::MockDht::new(core::iter::once(dht_pio::mock::MockResponse::Frame(frame)))
+}
+
+/// Frames of a model, see `dht_pio::encode`.
+pub trait Encode: Model {
+    fn encode(reading: &DhtResult<Self::Temperature, Self::Humidity>) -> [u8; 5];
+}
+
+impl Encode for model::Dht22 {
+    fn encode(reading: &DhtResult<f32, f32>) -> [u8; 5] {
+        encode::dht22(reading)
+    }
+}
+
+impl Encode for model::Dht22Type2 {
+    fn encode(reading: &DhtResult<f32, f32>) -> [u8; 5] {
+        encode::dht22_type2(reading)
+    }
+}
+
+impl Encode for model::Dht11 {
+    fn encode(reading: &DhtResult<u16, u16>) -> [u8; 5] {
+        encode::dht11(reading)
+    }
+}
+
+/// Sends `reading` as a sensor of model `M` through the transport built by `transport`, and
+/// checks that the sensor reads it back.
+pub fn assert_round_trip<M: Encode, T: DhtTransport>(
+    reading: DhtResult<M::Temperature, M::Humidity>,
+    transport: impl FnOnce([u8; 5]) -> T,
+) where
+    DhtResult<M::Temperature, M::Humidity>: PartialEq + Debug,
+{
+    let mut dht = Sensor::<M, T>::from_transport(transport(M::encode(&reading)));
+
+    assert_eq!(dht.read(&mut NoopDelay::new()), Ok(reading));
+}
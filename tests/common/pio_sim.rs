@@ -0,0 +1,363 @@
+//! Cycle-accurate interpreter for the subset of PIO used by `dht.pio` and `emulator.pio`.
+//!
+//! The state machine drives a single pin (used as `in`, `out` and `set` base), clocked at 1MHz like
+//! on the real hardware: one cycle is one microsecond. The line is open-drain: it is low when the
+//! state machine or the [`Sensor`] pulls it low, and high otherwise (pull-up).
+
+use std::collections::VecDeque;
+
+use pio::{
+    Instruction, InstructionOperands, JmpCondition, MovDestination, MovOperation, MovSource,
+    OutDestination, SetDestination, SideSet, WaitSource,
+};
+use pio_proc::pio_file;
+
+const FIFO_DEPTH: usize = 4;
+const PUSH_THRESHOLD: u8 = 32;
+
+/// Device connected to the simulated line.
+pub trait Sensor {
+    /// Called once per cycle, returns `true` if the sensor pulls the line low.
+    ///
+    /// `host_low` is `true` when the state machine pulls the line low.
+    fn pulls_low(&mut self, now_us: u64, host_low: bool) -> bool;
+}
+
+pub struct PioSim<S: Sensor> {
+    /// Encoded instructions, decoded on each step (`Instruction` is not `Clone`).
+    program: Vec<u16>,
+    side_set: SideSet,
+    wrap_source: u8,
+    wrap_target: u8,
+    /// `mov x, status` is all ones while the TX FIFO holds less words (`MovStatusConfig::Tx`).
+    status_tx_level: usize,
+
+    pc: u8,
+    x: u32,
+    y: u32,
+    isr: u32,
+    isr_count: u8,
+    osr: u32,
+    osr_count: u8,
+    delay: u8,
+
+    pin_dir: bool,
+    pin_out: bool,
+    line: bool,
+
+    pub tx_fifo: VecDeque<u32>,
+    pub rx_fifo: VecDeque<u32>,
+
+    now_us: u64,
+    pub sensor: S,
+}
+
+impl<S: Sensor> PioSim<S> {
+    /// Loads `src/dht.pio`.
+    pub fn dht(sensor: S) -> Self {
+        Self::load(&pio_file!("./src/dht.pio").program, 0, sensor)
+    }
+
+    /// Loads `src/emulator.pio`, the pin is also the `jmp` pin.
+    pub fn emulator(sensor: S) -> Self {
+        Self::load(&pio_file!("./src/emulator.pio").program, 1, sensor)
+    }
+
+    fn load<const N: usize>(program: &pio::Program<N>, status_tx_level: usize, sensor: S) -> Self {
+        for &code in &program.code {
+            Instruction::decode(code, program.side_set).expect("invalid instruction");
+        }
+
+        Self {
+            program: program.code.to_vec(),
+            side_set: program.side_set,
+            wrap_source: program.wrap.source,
+            wrap_target: program.wrap.target,
+            status_tx_level,
+            pc: 0,
+            x: 0,
+            y: 0,
+            isr: 0,
+            isr_count: 0,
+            osr: 0,
+            osr_count: 32,
+            delay: 0,
+            pin_dir: false,
+            pin_out: false,
+            line: true,
+            tx_fifo: VecDeque::new(),
+            rx_fifo: VecDeque::new(),
+            now_us: 0,
+            sensor,
+        }
+    }
+
+    pub fn now_us(&self) -> u64 {
+        self.now_us
+    }
+
+    /// Level of the line during the last cycle.
+    pub fn line(&self) -> bool {
+        self.line
+    }
+
+    /// Runs one cycle.
+    pub fn step(&mut self) {
+        let host_low = self.pin_dir && !self.pin_out;
+        let sensor_low = self.sensor.pulls_low(self.now_us, host_low);
+        self.line = !(host_low || sensor_low);
+
+        if self.delay > 0 {
+            self.delay -= 1;
+        } else {
+            self.execute();
+        }
+
+        self.now_us += 1;
+    }
+
+    /// Runs until `done` returns `true`, or `timeout_us` elapsed. Returns `true` if `done` was reached.
+    pub fn run_until(&mut self, timeout_us: u64, mut done: impl FnMut(&Self) -> bool) -> bool {
+        let deadline = self.now_us + timeout_us;
+
+        while self.now_us < deadline {
+            if done(self) {
+                return true;
+            }
+            self.step();
+        }
+
+        done(self)
+    }
+
+    /// Same sequence as `DhtPio::transfer`: writes the start signal length and waits for the two
+    /// words pushed by the program.
+    pub fn transfer(&mut self, start_signal_length: u32) -> Option<[u8; 5]> {
+        self.tx_fifo.push_back(start_signal_length - 1);
+
+        let timeout = u64::from(start_signal_length + 10) * 1000;
+        if !self.run_until(timeout, |sim| sim.rx_fifo.len() >= 2) {
+            return None;
+        }
+
+        let data = self.rx_fifo.pop_front()?;
+        let crc = self.rx_fifo.pop_front()?;
+        let [b0, b1, b2, b3] = data.to_be_bytes();
+        Some([b0, b1, b2, b3, crc as u8])
+    }
+
+    fn execute(&mut self) {
+        let instruction = Instruction::decode(self.program[usize::from(self.pc)], self.side_set)
+            .expect("checked by load");
+        let mut next_pc = if self.pc == self.wrap_source {
+            self.wrap_target
+        } else {
+            self.pc + 1
+        };
+
+        match instruction.operands {
+            InstructionOperands::JMP { condition, address } => {
+                let taken = match condition {
+                    JmpCondition::Always => true,
+                    JmpCondition::XIsZero => self.x == 0,
+                    JmpCondition::XDecNonZero => {
+                        let taken = self.x != 0;
+                        self.x = self.x.wrapping_sub(1);
+                        taken
+                    }
+                    JmpCondition::YIsZero => self.y == 0,
+                    JmpCondition::YDecNonZero => {
+                        let taken = self.y != 0;
+                        self.y = self.y.wrapping_sub(1);
+                        taken
+                    }
+                    JmpCondition::XNotEqualY => self.x != self.y,
+                    JmpCondition::PinHigh => self.line,
+                    JmpCondition::OutputShiftRegisterNotEmpty => self.osr_count < 32,
+                };
+
+                if taken {
+                    next_pc = address;
+                }
+            }
+            InstructionOperands::WAIT {
+                polarity,
+                source: WaitSource::PIN | WaitSource::GPIO,
+                ..
+            } => {
+                if self.line != (polarity == 1) {
+                    return;
+                }
+            }
+            InstructionOperands::IN { source, bit_count } => {
+                let value = self.mov_source(source_of_in(source));
+                let bit_count = if bit_count == 0 { 32 } else { bit_count };
+                let mask = if bit_count == 32 {
+                    u32::MAX
+                } else {
+                    (1 << bit_count) - 1
+                };
+
+                self.isr =
+                    (self.isr.checked_shl(u32::from(bit_count)).unwrap_or(0)) | (value & mask);
+                self.isr_count = (self.isr_count + bit_count).min(32);
+            }
+            InstructionOperands::OUT {
+                destination,
+                bit_count,
+            } => {
+                let bit_count = if bit_count == 0 { 32 } else { bit_count };
+                let value = self
+                    .osr
+                    .checked_shr(32 - u32::from(bit_count))
+                    .unwrap_or(self.osr);
+                self.osr = self.osr.checked_shl(u32::from(bit_count)).unwrap_or(0);
+                self.osr_count = (self.osr_count + bit_count).min(32);
+
+                match destination {
+                    OutDestination::PINS => self.pin_out = value & 1 == 1,
+                    OutDestination::X => self.x = value,
+                    OutDestination::Y => self.y = value,
+                    OutDestination::NULL => (),
+                    OutDestination::PINDIRS => self.pin_dir = value & 1 == 1,
+                    OutDestination::PC => next_pc = value as u8,
+                    OutDestination::ISR => {
+                        self.isr = value;
+                        self.isr_count = bit_count;
+                    }
+                    OutDestination::EXEC => unsupported(&instruction),
+                }
+            }
+            InstructionOperands::PUSH { if_full, block } => {
+                if !if_full || self.isr_count >= PUSH_THRESHOLD {
+                    if self.rx_fifo.len() < FIFO_DEPTH {
+                        self.rx_fifo.push_back(self.isr);
+                    } else if block {
+                        return;
+                    }
+
+                    self.isr = 0;
+                    self.isr_count = 0;
+                }
+            }
+            InstructionOperands::PULL { block, .. } => match self.tx_fifo.pop_front() {
+                Some(value) => {
+                    self.osr = value;
+                    self.osr_count = 0;
+                }
+                None if block => return,
+                None => {
+                    self.osr = self.x;
+                    self.osr_count = 0;
+                }
+            },
+            InstructionOperands::MOV {
+                destination,
+                op,
+                source,
+            } => {
+                let value = self.mov_source(source);
+                let value = match op {
+                    MovOperation::None => value,
+                    MovOperation::Invert => !value,
+                    MovOperation::BitReverse => value.reverse_bits(),
+                };
+
+                match destination {
+                    MovDestination::X => self.x = value,
+                    MovDestination::Y => self.y = value,
+                    MovDestination::PINS => self.pin_out = value & 1 == 1,
+                    MovDestination::PC => next_pc = value as u8,
+                    MovDestination::ISR => {
+                        self.isr = value;
+                        self.isr_count = 0;
+                    }
+                    MovDestination::OSR => {
+                        self.osr = value;
+                        self.osr_count = 0;
+                    }
+                    MovDestination::EXEC => unsupported(&instruction),
+                }
+            }
+            InstructionOperands::SET { destination, data } => match destination {
+                SetDestination::PINS => self.pin_out = data & 1 == 1,
+                SetDestination::PINDIRS => self.pin_dir = data & 1 == 1,
+                SetDestination::X => self.x = u32::from(data),
+                SetDestination::Y => self.y = u32::from(data),
+            },
+            _ => unsupported(&instruction),
+        }
+
+        self.delay = instruction.delay;
+        self.pc = next_pc;
+    }
+
+    fn mov_source(&self, source: MovSource) -> u32 {
+        match source {
+            MovSource::PINS => u32::from(self.line),
+            MovSource::X => self.x,
+            MovSource::Y => self.y,
+            MovSource::NULL => 0,
+            MovSource::STATUS if self.tx_fifo.len() < self.status_tx_level => u32::MAX,
+            MovSource::STATUS => 0,
+            MovSource::ISR => self.isr,
+            MovSource::OSR => self.osr,
+        }
+    }
+}
+
+fn unsupported(instruction: &Instruction) -> ! {
+    panic!("unsupported instruction: {instruction:?}")
+}
+
+fn source_of_in(source: pio::InSource) -> MovSource {
+    match source {
+        pio::InSource::PINS => MovSource::PINS,
+        pio::InSource::X => MovSource::X,
+        pio::InSource::Y => MovSource::Y,
+        pio::InSource::NULL => MovSource::NULL,
+        pio::InSource::ISR => MovSource::ISR,
+        pio::InSource::OSR => MovSource::OSR,
+    }
+}
+
+impl<S: Sensor> dht_pio::Transport for PioSim<S> {}
+
+impl<S: Sensor> dht_pio::DhtTransport for PioSim<S> {
+    fn transfer<D: embedded_hal::delay::DelayNs>(
+        &mut self,
+        start_signal_length: u32,
+        _delay: &mut D,
+    ) -> Result<[u8; 5], dht_pio::DhtError> {
+        PioSim::transfer(self, start_signal_length).ok_or(dht_pio::DhtError::Timeout)
+    }
+}
+
+/// Host line of a [`PioSim`] used as the sensor of another one (e.g. the emulator read by the DHT program).
+#[derive(Default)]
+pub struct HostLine {
+    /// `true` when the host pulls the line low.
+    pub low: bool,
+}
+
+impl Sensor for HostLine {
+    fn pulls_low(&mut self, _now_us: u64, _host_low: bool) -> bool {
+        self.low
+    }
+}
+
+impl Sensor for PioSim<HostLine> {
+    fn pulls_low(&mut self, _now_us: u64, host_low: bool) -> bool {
+        let low = self.pin_dir && !self.pin_out;
+        self.sensor.low = host_low;
+        self.step();
+        low
+    }
+}
+
+/// The simulation has its own clock, nothing to wait for.
+pub struct NoDelay;
+
+impl embedded_hal::delay::DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
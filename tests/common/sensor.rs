@@ -0,0 +1,68 @@
+//! Simulated DHT sensor, answering a start signal with a [`Waveform`].
+
+use super::pio_sim::Sensor;
+use super::waveform::{Segment, Waveform};
+
+pub struct SimSensor {
+    segments: Vec<Segment>,
+    /// Shortest start signal the sensor reacts to (in µs).
+    min_start_signal: u32,
+
+    low_since: Option<u64>,
+    /// Current answer: (pull low, end time).
+    answer: Vec<(bool, u64)>,
+}
+
+impl SimSensor {
+    pub fn new(waveform: &Waveform) -> Self {
+        Self {
+            segments: waveform.segments(),
+            min_start_signal: 800,
+            low_since: None,
+            answer: Vec::new(),
+        }
+    }
+
+    pub fn min_start_signal(mut self, min_start_signal_us: u32) -> Self {
+        self.min_start_signal = min_start_signal_us;
+        self
+    }
+
+    fn start_answer(&mut self, now_us: u64) {
+        let mut t = now_us;
+
+        self.answer = self
+            .segments
+            .iter()
+            .map(|s| {
+                t += u64::from(s.length_us);
+                (s.low, t)
+            })
+            .collect();
+    }
+}
+
+impl Sensor for SimSensor {
+    fn pulls_low(&mut self, now_us: u64, host_low: bool) -> bool {
+        if host_low {
+            self.answer.clear();
+            self.low_since.get_or_insert(now_us);
+            return false;
+        }
+
+        if let Some(since) = self.low_since.take() {
+            if now_us - since >= u64::from(self.min_start_signal) {
+                self.start_answer(now_us);
+            }
+        }
+
+        while let Some(&(low, end)) = self.answer.first() {
+            if now_us < end {
+                return low;
+            }
+            self.answer.remove(0);
+        }
+
+        false
+    }
+}
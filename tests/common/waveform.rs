@@ -0,0 +1,170 @@
+//! Timed waveform emitted by a DHT sensor after a start signal.
+
+use dht_pio::DhtResult;
+
+/// Timings of the sensor answer (in µs), defaults are the typical values of the datasheets.
+#[derive(Debug, Clone, Copy)]
+pub struct Timing {
+    /// Time between the release of the line by the host and the ACK.
+    pub response_delay: u32,
+    pub ack_low: u32,
+    pub ack_high: u32,
+    pub bit_low: u32,
+    pub zero_high: u32,
+    pub one_high: u32,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self {
+            response_delay: 30,
+            ack_low: 80,
+            ack_high: 80,
+            bit_low: 50,
+            zero_high: 27,
+            one_high: 70,
+        }
+    }
+}
+
+/// Part of the waveform where the line is pulled low (`low == true`) or released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub low: bool,
+    pub length_us: u32,
+}
+
+/// Builder of the waveform of a frame.
+#[derive(Debug, Clone)]
+pub struct Waveform {
+    frame: [u8; 5],
+    timing: Timing,
+    ack: bool,
+    jitter: u32,
+    seed: u32,
+    /// (bit index, position in the high part of the bit, length) of low glitches.
+    glitches: Vec<(usize, u32, u32)>,
+    dropped_bits: Vec<usize>,
+}
+
+impl Waveform {
+    pub fn from_frame(frame: [u8; 5]) -> Self {
+        Self {
+            frame,
+            timing: Timing::default(),
+            ack: true,
+            jitter: 0,
+            seed: 0x1234_5678,
+            glitches: Vec::new(),
+            dropped_bits: Vec::new(),
+        }
+    }
+
+    pub fn dht22(reading: &DhtResult<f32, f32>) -> Self {
+        Self::from_frame(dht_pio::encode::dht22(reading))
+    }
+
+    pub fn dht22_type2(reading: &DhtResult<f32, f32>) -> Self {
+        Self::from_frame(dht_pio::encode::dht22_type2(reading))
+    }
+
+    pub fn dht11(reading: &DhtResult<u16, u16>) -> Self {
+        Self::from_frame(dht_pio::encode::dht11(reading))
+    }
+
+    pub fn frame(&self) -> [u8; 5] {
+        self.frame
+    }
+
+    pub fn timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Each pulse is randomly lengthened or shortened by up to `jitter_us`.
+    pub fn jitter(mut self, jitter_us: u32, seed: u32) -> Self {
+        self.jitter = jitter_us;
+        self.seed = seed.max(1);
+        self
+    }
+
+    /// The sensor never answers.
+    pub fn without_ack(mut self) -> Self {
+        self.ack = false;
+        self
+    }
+
+    /// Pulls the line low for `length_us`, `at_us` after the rising edge of the bit `bit` (0 is the MSB of the frame).
+    pub fn glitch(mut self, bit: usize, at_us: u32, length_us: u32) -> Self {
+        self.glitches.push((bit, at_us, length_us));
+        self
+    }
+
+    /// The bit `bit` (0 is the MSB of the frame) is not sent.
+    pub fn drop_bit(mut self, bit: usize) -> Self {
+        self.dropped_bits.push(bit);
+        self
+    }
+
+    /// Segments of the answer, starting when the host releases the line.
+    pub fn segments(&self) -> Vec<Segment> {
+        let mut rng = self.seed;
+        let mut segments = Vec::new();
+
+        if !self.ack {
+            return segments;
+        }
+
+        let mut push = |low: bool, length: u32| {
+            let length = jittered(length, self.jitter, &mut rng);
+            segments.push(Segment {
+                low,
+                length_us: length,
+            });
+        };
+
+        push(false, self.timing.response_delay);
+        push(true, self.timing.ack_low);
+        push(false, self.timing.ack_high);
+
+        for bit in 0..40 {
+            if self.dropped_bits.contains(&bit) {
+                continue;
+            }
+
+            let high = if self.frame[bit / 8] & (0x80 >> (bit % 8)) != 0 {
+                self.timing.one_high
+            } else {
+                self.timing.zero_high
+            };
+
+            push(true, self.timing.bit_low);
+
+            match self.glitches.iter().find(|(b, _, _)| *b == bit) {
+                Some(&(_, at, length)) if at < high => {
+                    push(false, at);
+                    push(true, length);
+                    push(false, high - at);
+                }
+                _ => push(false, high),
+            }
+        }
+
+        push(true, self.timing.bit_low);
+        segments
+    }
+}
+
+fn jittered(length: u32, jitter: u32, rng: &mut u32) -> u32 {
+    if jitter == 0 {
+        return length;
+    }
+
+    // xorshift32
+    *rng ^= *rng << 13;
+    *rng ^= *rng >> 17;
+    *rng ^= *rng << 5;
+
+    let offset = i64::from(*rng % (2 * jitter + 1)) - i64::from(jitter);
+    (i64::from(length) + offset).max(1) as u32
+}
@@ -0,0 +1,63 @@
+use dht_pio::condensation::{Detector, Risk};
+use dht_pio::DhtResult;
+
+// Dew point of 9.3°C
+const AIR: DhtResult<f32, f32> = DhtResult {
+    temperature: 20.0,
+    humidity: 50.0,
+};
+
+#[test]
+fn condensation_levels() {
+    let mut detector = Detector::new().confirm(1);
+
+    let assessment = detector.update(&AIR, 15.0);
+    assert!((assessment.condensation_margin - 5.7).abs() < 0.1);
+    assert_eq!(assessment.condensation, Risk::None);
+
+    assert_eq!(detector.update(&AIR, 11.0).condensation, Risk::Low);
+    assert_eq!(detector.update(&AIR, 9.0).condensation, Risk::High);
+    // Within the hysteresis
+    assert_eq!(detector.update(&AIR, 10.5).condensation, Risk::High);
+    assert_eq!(detector.update(&AIR, 11.0).condensation, Risk::Low);
+    assert_eq!(detector.update(&AIR, 13.0).condensation, Risk::None);
+}
+
+#[test]
+fn single_bad_frame() {
+    let mut detector = Detector::new();
+
+    let bad = DhtResult {
+        temperature: 20.0,
+        humidity: 99.9,
+    };
+
+    assert_eq!(detector.update(&AIR, 15.0).condensation, Risk::None);
+    assert_eq!(detector.update(&bad, 15.0).condensation, Risk::None);
+    assert_eq!(detector.update(&AIR, 15.0).condensation, Risk::None);
+    assert_eq!(detector.update(&bad, 15.0).condensation, Risk::None);
+    assert_eq!(detector.update(&bad, 15.0).condensation, Risk::High);
+}
+
+#[test]
+fn frost() {
+    let mut detector = Detector::new().confirm(1);
+
+    let cold = DhtResult {
+        temperature: 1.0,
+        humidity: 80.0,
+    };
+    // Frost point above the dew point below 0°C
+    assert!(cold.frost_point() > cold.dew_point());
+
+    // Surface above 0°C
+    assert_eq!(detector.update(&cold, 5.0).frost, Risk::None);
+    assert_eq!(detector.update(&cold, -1.5).frost, Risk::High);
+    assert_eq!(detector.frost(), Risk::High);
+
+    let dry = DhtResult {
+        temperature: 2.0,
+        humidity: 20.0,
+    };
+    assert_eq!(detector.update(&dry, -5.0).frost, Risk::None);
+}
@@ -0,0 +1,62 @@
+//! Property-based tests of the decoding done by the sensors.
+
+mod common;
+
+use common::{answering, assert_round_trip, reading};
+use dht_pio::mock::MockDelay;
+use dht_pio::{encode, model, Dht11, Dht22, Dht22Type2, DhtError};
+use proptest::prelude::*;
+
+fn flip(mut frame: [u8; 5], bit: usize) -> [u8; 5] {
+    frame[bit / 8] ^= 0x80 >> (bit % 8);
+    frame
+}
+
+proptest! {
+    #[test]
+    fn dht22_round_trip(temperature in -400_i16..=800, humidity in 0_u16..=1000) {
+        let reading = reading(f32::from(temperature) / 10.0, f32::from(humidity) / 10.0);
+        assert_round_trip::<model::Dht22, _>(reading, answering);
+    }
+
+    #[test]
+    fn dht22_type2_round_trip(temperature in -400_i16..=800, humidity in 0_u16..=1000) {
+        let reading = reading(f32::from(temperature) / 10.0, f32::from(humidity) / 10.0);
+        assert_round_trip::<model::Dht22Type2, _>(reading, answering);
+    }
+
+    #[test]
+    fn dht11_round_trip(temperature in 0_u16..=0xFF, humidity in 0_u16..=0xFF) {
+        assert_round_trip::<model::Dht11, _>(reading(temperature, humidity), answering);
+    }
+
+    #[test]
+    fn single_bit_flip_is_detected(raw_temp: u16, raw_hum: u16, bit in 0_usize..40) {
+        let frame = flip(encode::raw_frame(raw_temp, raw_hum), bit);
+
+        prop_assert!(matches!(
+            Dht22::from_transport(answering(frame)).read(&mut MockDelay::default()),
+            Err(DhtError::CrcMismatch(_, _))
+        ));
+        prop_assert!(matches!(
+            Dht22Type2::from_transport(answering(frame)).read(&mut MockDelay::default()),
+            Err(DhtError::CrcMismatch(_, _))
+        ));
+        prop_assert!(matches!(
+            Dht11::from_transport(answering(frame)).read(&mut MockDelay::default()),
+            Err(DhtError::CrcMismatch(_, _))
+        ));
+    }
+
+    #[test]
+    fn any_frame_decodes_or_fails(frame: [u8; 5]) {
+        let valid = encode::raw_frame(
+            u16::from_be_bytes([frame[2], frame[3]]),
+            u16::from_be_bytes([frame[0], frame[1]]),
+        ) == frame;
+
+        prop_assert_eq!(Dht22::from_transport(answering(frame)).read(&mut MockDelay::default()).is_ok(), valid);
+        prop_assert_eq!(Dht22Type2::from_transport(answering(frame)).read(&mut MockDelay::default()).is_ok(), valid);
+        prop_assert_eq!(Dht11::from_transport(answering(frame)).read(&mut MockDelay::default()).is_ok(), valid);
+    }
+}
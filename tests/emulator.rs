@@ -0,0 +1,106 @@
+mod common;
+
+use common::pio_sim::{HostLine, PioSim};
+use common::{assert_round_trip, reading, DHT11_START_SIGNAL, DHT22_START_SIGNAL, FRAME};
+use dht_pio::{encode, model, DhtResult};
+
+/// The DHT program reading the emulator.
+fn sim() -> PioSim<PioSim<HostLine>> {
+    PioSim::dht(PioSim::emulator(HostLine::default()))
+}
+
+/// Same as `DhtEmulator::update`.
+fn load(emulator: &mut PioSim<HostLine>, frame: [u8; 5]) {
+    let [b0, b1, b2, b3, crc] = frame;
+    emulator
+        .tx_fifo
+        .push_back(u32::from_be_bytes([b0, b1, b2, b3]));
+    emulator.tx_fifo.push_back(u32::from(crc));
+}
+
+#[test]
+fn answers_with_the_frame() {
+    let mut sim = sim();
+    load(&mut sim.sensor, FRAME);
+
+    assert_eq!(sim.transfer(DHT22_START_SIGNAL), Some(FRAME));
+    assert_eq!(sim.transfer(DHT11_START_SIGNAL), None);
+}
+
+#[test]
+fn answers_consecutive_reads() {
+    let mut sim = sim();
+
+    for temperature in [-10.0, 0.0, 25.3] {
+        let frame = encode::dht22(&DhtResult {
+            temperature,
+            humidity: 50.0,
+        });
+        load(&mut sim.sensor, frame);
+
+        assert_eq!(sim.transfer(DHT22_START_SIGNAL), Some(frame));
+        sim.run_until(2_000, |_| false);
+        assert!(sim.sensor.tx_fifo.is_empty());
+    }
+}
+
+#[test]
+fn line_is_released_after_the_frame() {
+    let mut sim = sim();
+    load(&mut sim.sensor, FRAME);
+
+    assert_eq!(sim.transfer(DHT22_START_SIGNAL), Some(FRAME));
+    sim.run_until(200, |_| false);
+    assert!(!sim.run_until(10_000, |sim| !sim.line()));
+}
+
+/// Start signal of the host, long enough for any model.
+fn start_signal(emulator: &mut PioSim<HostLine>) {
+    emulator.sensor.low = true;
+    emulator.run_until(1_000, |_| false);
+    emulator.sensor.low = false;
+    emulator.step();
+}
+
+#[test]
+fn does_not_answer_without_frame() {
+    let mut emulator = PioSim::emulator(HostLine::default());
+    emulator.run_until(100, |_| false);
+
+    start_signal(&mut emulator);
+    assert!(!emulator.run_until(1_000, |sim| !sim.line()));
+
+    // A frame buffered after the start signal is only sent on the next one
+    load(&mut emulator, FRAME);
+    assert!(!emulator.run_until(10_000, |sim| !sim.line()));
+    start_signal(&mut emulator);
+    assert!(emulator.run_until(1_000, |sim| !sim.line()));
+}
+
+#[test]
+fn ignores_short_start_signals() {
+    let mut emulator = PioSim::emulator(HostLine::default());
+    load(&mut emulator, FRAME);
+    emulator.run_until(100, |_| false);
+
+    emulator.sensor.low = true;
+    emulator.run_until(300, |_| false);
+    emulator.sensor.low = false;
+    emulator.step();
+
+    assert!(!emulator.run_until(1_000, |sim| !sim.line()));
+    assert_eq!(emulator.tx_fifo.len(), 2);
+}
+
+#[test]
+fn round_trips() {
+    let emulated = |frame| {
+        let mut sim = sim();
+        load(&mut sim.sensor, frame);
+        sim
+    };
+
+    assert_round_trip::<model::Dht22, _>(reading(-12.3, 45.6), emulated);
+    assert_round_trip::<model::Dht22Type2, _>(reading(-10.5, 99.9), emulated);
+    assert_round_trip::<model::Dht11, _>(reading(23, 41), emulated);
+}
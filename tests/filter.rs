@@ -0,0 +1,71 @@
+mod common;
+
+use common::reading;
+use dht_pio::filter::{Ema, Filter, Median, RateLimit};
+
+#[test]
+fn median_rejects_a_spike() {
+    let mut median = Median::<f32, f32, 3>::new();
+
+    assert_eq!(median.update(reading(20.0, 50.0)), Some(reading(20.0, 50.0)));
+    assert_eq!(median.update(reading(20.2, 51.0)), Some(reading(20.0, 50.0)));
+    assert_eq!(median.update(reading(35.0, 50.5)), Some(reading(20.2, 50.5)));
+    assert_eq!(median.update(reading(20.1, 50.0)), Some(reading(20.2, 50.5)));
+}
+
+#[test]
+fn median_of_integers() {
+    let mut median = Median::<u16, u16, 5>::new();
+
+    for (t, h) in [(21, 40), (80, 41), (22, 90), (20, 42), (23, 40)] {
+        median.update(reading(t, h));
+    }
+
+    assert_eq!(median.update(reading(21, 41)), Some(reading(22, 41)));
+}
+
+#[test]
+fn ema_float() {
+    let mut ema = Ema::new(64);
+
+    assert_eq!(ema.update(reading(20.0, 40.0)), Some(reading(20.0, 40.0)));
+    assert_eq!(ema.update(reading(24.0, 48.0)), Some(reading(21.0, 42.0)));
+}
+
+#[test]
+fn ema_integer_reaches_the_target() {
+    let mut ema = Ema::<u16, u16>::new(64);
+    ema.update(reading(20, 60));
+
+    let mut last = None;
+    for _ in 0..30 {
+        last = ema.update(reading(25, 50));
+    }
+
+    assert_eq!(last, Some(reading(25, 50)));
+}
+
+#[test]
+fn rate_limit() {
+    let mut limit = RateLimit::new(2.0, 5.0).accept_after(2);
+
+    assert!(limit.update(reading(20.0, 50.0)).is_some());
+    assert!(limit.update(reading(21.5, 54.0)).is_some());
+    assert!(limit.update(reading(30.0, 54.0)).is_none());
+    assert!(limit.update(reading(21.0, 70.0)).is_none());
+    // Accepted after 2 rejections
+    assert!(limit.update(reading(30.0, 54.0)).is_some());
+    assert!(limit.update(reading(31.0, 54.0)).is_some());
+}
+
+#[test]
+fn chain() {
+    let mut filter = RateLimit::new(2.0, 5.0).then(Median::<f32, f32, 3>::new());
+
+    assert_eq!(filter.update(reading(20.0, 50.0)), Some(reading(20.0, 50.0)));
+    assert_eq!(filter.update(reading(40.0, 50.0)), None);
+    assert_eq!(filter.update(reading(21.0, 52.0)), Some(reading(20.0, 50.0)));
+
+    filter.reset();
+    assert_eq!(filter.update(reading(40.0, 50.0)), Some(reading(40.0, 50.0)));
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use common::at;
+use dht_pio::health::{Health, HealthState, Transition};
+use dht_pio::{DhtError, Duration};
+
+const FAILURE: Result<(), DhtError> = Err(DhtError::Timeout);
+const SUCCESS: Result<(), DhtError> = Ok(());
+
+#[test]
+fn degraded_then_quarantined() {
+    let mut health = Health::new().degraded_after(2).quarantined_after(4);
+
+    assert_eq!(health.record(&FAILURE, at(0)), None);
+    assert_eq!(
+        health.record(&FAILURE, at(1)),
+        Some(Transition {
+            from: HealthState::Healthy,
+            to: HealthState::Degraded
+        })
+    );
+    assert_eq!(health.record(&FAILURE, at(2)), None);
+    assert_eq!(
+        health.record(&FAILURE, at(3)),
+        Some(Transition {
+            from: HealthState::Degraded,
+            to: HealthState::Quarantined
+        })
+    );
+    assert_eq!(health.state(), HealthState::Quarantined);
+}
+
+#[test]
+fn exponential_backoff() {
+    let mut health = Health::new()
+        .degraded_after(1)
+        .quarantined_after(100)
+        .backoff(Duration::secs(2), Duration::secs(10));
+
+    let mut now = at(0);
+    for expected in [2, 4, 8, 10, 10] {
+        health.record(&FAILURE, now);
+        let next = health.next_attempt().unwrap();
+        assert_eq!((next - now).to_secs(), expected);
+
+        assert!(!health.should_read(next - Duration::micros(1)));
+        assert!(health.should_read(next));
+        now = next;
+    }
+}
+
+#[test]
+fn quarantined_sensor_is_probed() {
+    let mut health = Health::new()
+        .quarantined_after(1)
+        .probe_interval(Duration::secs(60));
+
+    health.record(&FAILURE, at(0));
+    assert!(!health.should_read(at(59)));
+    assert!(health.should_read(at(60)));
+
+    health.record(&FAILURE, at(60));
+    assert_eq!(health.next_attempt(), Some(at(120)));
+}
+
+#[test]
+fn success_recovers() {
+    let mut health = Health::new().quarantined_after(1);
+    health.record(&FAILURE, at(0));
+
+    assert_eq!(
+        health.record(&SUCCESS, at(300)),
+        Some(Transition {
+            from: HealthState::Quarantined,
+            to: HealthState::Healthy
+        })
+    );
+    assert_eq!(health.consecutive_failures(), 0);
+    assert!(health.should_read(at(300)));
+    assert_eq!(health.record(&SUCCESS, at(302)), None);
+}
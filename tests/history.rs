@@ -0,0 +1,92 @@
+mod common;
+
+use common::{at, reading};
+use dht_pio::history::History;
+use dht_pio::Duration;
+
+#[test]
+fn ring_buffer() {
+    let mut history = History::<3>::new(Duration::secs(2));
+    assert!(history.summary().is_none());
+
+    for (i, t) in [1.0, 2.0, 3.0, 4.0].into_iter().enumerate() {
+        history.push(reading(t, 50.0), at(2 * i as u64));
+    }
+
+    assert!(history.is_full());
+    let temperatures: Vec<f32> = history.iter().map(|s| s.reading.temperature).collect();
+    assert_eq!(temperatures, [2.0, 3.0, 4.0]);
+    assert_eq!(history.latest().unwrap().at, at(6));
+}
+
+#[test]
+fn summary() {
+    let mut history = History::<8>::new(Duration::secs(2));
+    for (i, t) in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]
+        .into_iter()
+        .enumerate()
+    {
+        history.push(reading(t, 40.0 + i as f32), at(2 * i as u64));
+    }
+
+    let summary = history.summary().unwrap();
+    assert_eq!(summary.temperature.min, 2.0);
+    assert_eq!(summary.temperature.max, 9.0);
+    assert_eq!(summary.temperature.mean, 5.0);
+    assert_eq!(summary.temperature.std_dev, 2.0);
+    assert_eq!(summary.humidity.min, 40.0);
+    assert_eq!(summary.humidity.max, 47.0);
+}
+
+#[test]
+fn integer_readings() {
+    let mut history = History::<4, u16, u16>::new(Duration::secs(1));
+    history.push(reading(20, 40), at(0));
+    history.push(reading(22, 44), at(1));
+
+    let summary = history.summary().unwrap();
+    assert_eq!(summary.temperature.min, 20);
+    assert_eq!(summary.temperature.mean, 21.0);
+    assert_eq!(summary.humidity.std_dev, 2.0);
+}
+
+#[test]
+fn last_hour() {
+    let mut history = History::<16>::new(Duration::secs(600));
+    for (i, t) in [30.0, 10.0, 20.0, 21.0, 22.0, 19.0, 20.0, 23.0]
+        .into_iter()
+        .enumerate()
+    {
+        history.push(reading(t, 50.0), at(600 * i as u64));
+    }
+
+    let last_hour = history.last_hour(at(4800)).unwrap();
+    assert_eq!(last_hour.temperature.min, 19.0);
+    assert_eq!(last_hour.temperature.max, 23.0);
+}
+
+#[test]
+fn missing_samples() {
+    let mut history = History::<8>::new(Duration::secs(2));
+    for secs in [0, 2, 4, 10, 12, 16] {
+        history.push(reading(20.0, 50.0), at(secs));
+    }
+
+    // 6, 8 and 14 missing
+    assert_eq!(history.missing(), 3);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let mut history = History::<4>::new(Duration::secs(2));
+    for i in 0..6 {
+        history.push(reading(20.0 + i as f32, 50.0), at(2 * i));
+    }
+
+    let json = serde_json::to_string(&history).unwrap();
+    let restored: History<4> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.interval(), history.interval());
+    assert!(restored.iter().eq(history.iter()));
+}
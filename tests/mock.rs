@@ -0,0 +1,56 @@
+use dht_pio::mock::{MockDht, MockResponse};
+use dht_pio::{Dht22, DhtError, Instant};
+
+fn at_ms(ms: u64) -> Instant {
+    Instant::from_ticks(ms * 1000)
+}
+
+#[test]
+fn conversion_answered_after_its_delay() {
+    let script = [
+        MockResponse::Delay(5),
+        MockResponse::Data {
+            humidity: 652,
+            temperature: 215,
+        },
+    ];
+    let mut dht = Dht22::from_transport(MockDht::new(script.into_iter()));
+
+    dht.start_conversion(at_ms(0));
+    assert_eq!(dht.poll(at_ms(4)), Err(nb::Error::WouldBlock));
+
+    let reading = dht.poll(at_ms(5)).unwrap();
+    assert_eq!(reading.temperature, 21.5);
+    assert_eq!(reading.humidity, 65.2);
+    // Nothing in progress
+    assert_eq!(dht.poll(at_ms(6)), Err(nb::Error::WouldBlock));
+}
+
+#[test]
+fn conversion_without_answer_times_out() {
+    let mut dht = Dht22::from_transport(MockDht::new([MockResponse::Timeout].into_iter()));
+
+    dht.start_conversion(at_ms(0));
+    // Start signal and answer time of the DHT22
+    assert_eq!(dht.poll(at_ms(10)), Err(nb::Error::WouldBlock));
+    assert_eq!(
+        dht.poll(at_ms(11)),
+        Err(nb::Error::Other(DhtError::Timeout))
+    );
+    assert_eq!(dht.stats().response_timeouts, 1);
+}
+
+#[test]
+fn frame_received_before_a_late_poll() {
+    let frame = [MockResponse::Data {
+        humidity: 652,
+        temperature: 215,
+    }];
+    let mut dht = Dht22::from_transport(MockDht::new(frame.into_iter()));
+
+    dht.start_conversion(at_ms(0));
+
+    // Single poll of a slow loop, long after the deadline
+    let reading = dht.poll(at_ms(100)).unwrap();
+    assert_eq!(reading.temperature, 21.5);
+}
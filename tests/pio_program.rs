@@ -0,0 +1,140 @@
+mod common;
+
+use common::pio_sim::{NoDelay, PioSim};
+use common::sensor::SimSensor;
+use common::waveform::{Timing, Waveform};
+use common::{assert_round_trip, reading, DHT11_START_SIGNAL, DHT22_START_SIGNAL, FRAME};
+use dht_pio::{model, Dht22, DhtError};
+
+fn sim(waveform: &Waveform) -> PioSim<SimSensor> {
+    PioSim::dht(SimSensor::new(waveform))
+}
+
+#[test]
+fn reads_a_frame() {
+    let mut sim = sim(&Waveform::from_frame(FRAME));
+
+    assert_eq!(sim.transfer(DHT22_START_SIGNAL), Some(FRAME));
+}
+
+#[test]
+fn reads_consecutive_frames() {
+    let mut sim = sim(&Waveform::from_frame(FRAME));
+
+    for _ in 0..3 {
+        assert_eq!(sim.transfer(DHT22_START_SIGNAL), Some(FRAME));
+        sim.run_until(2_000, |_| false);
+    }
+}
+
+#[test]
+fn start_signal_length() {
+    let mut sim = sim(&Waveform::from_frame(FRAME));
+    sim.run_until(100, |_| false);
+    sim.tx_fifo.push_back(DHT11_START_SIGNAL - 1);
+
+    assert!(sim.run_until(1_000, |sim| !sim.line()));
+    let start = sim.now_us();
+    assert!(sim.run_until(30_000, |sim| sim.line()));
+    let length = sim.now_us() - start;
+
+    assert!(
+        (18_000..19_000).contains(&length),
+        "start signal: {length}µs"
+    );
+    assert!(sim.run_until(10_000, |sim| sim.rx_fifo.len() == 2));
+}
+
+#[test]
+fn line_is_idle_high() {
+    let mut sim = sim(&Waveform::from_frame(FRAME));
+
+    sim.run_until(10_000, |_| false);
+    assert!(sim.line());
+    assert!(sim.rx_fifo.is_empty());
+
+    assert_eq!(sim.transfer(DHT22_START_SIGNAL), Some(FRAME));
+    sim.run_until(10_000, |_| false);
+    assert!(sim.line());
+}
+
+#[test]
+fn missing_ack() {
+    let mut sim = sim(&Waveform::from_frame(FRAME).without_ack());
+
+    assert_eq!(sim.transfer(DHT22_START_SIGNAL), None);
+    assert!(sim.rx_fifo.is_empty());
+}
+
+#[test]
+fn start_signal_too_short_for_the_sensor() {
+    let mut sim = PioSim::dht(SimSensor::new(&Waveform::from_frame(FRAME)).min_start_signal(1_500));
+
+    assert_eq!(sim.transfer(DHT22_START_SIGNAL), None);
+}
+
+#[test]
+fn bits_stretched_to_the_threshold() {
+    // `in pins, 1` samples the line 41µs after the rising edge
+    let timing = Timing {
+        zero_high: 40,
+        one_high: 43,
+        ..Timing::default()
+    };
+    let mut sim = sim(&Waveform::from_frame(FRAME).timing(timing));
+
+    assert_eq!(sim.transfer(DHT22_START_SIGNAL), Some(FRAME));
+}
+
+#[test]
+fn bits_beyond_the_threshold() {
+    let timing = Timing {
+        zero_high: 45,
+        ..Timing::default()
+    };
+    let mut sim = sim(&Waveform::from_frame(FRAME).timing(timing));
+
+    assert_eq!(sim.transfer(DHT22_START_SIGNAL), Some([0xFF; 5]));
+}
+
+#[test]
+fn timing_jitter() {
+    for seed in 1..50 {
+        let mut sim = sim(&Waveform::from_frame(FRAME).jitter(8, seed));
+
+        assert_eq!(sim.transfer(DHT22_START_SIGNAL), Some(FRAME), "seed {seed}");
+    }
+}
+
+#[test]
+fn glitch_in_a_bit() {
+    // The first bit of the temperature (bit 16) is a 0: a glitch after the sampling point splits it
+    // in two bits, shifting the rest of the frame
+    let mut dht = Dht22::from_transport(sim(&Waveform::from_frame(FRAME)
+        .glitch(16, 60, 2)
+        .timing(Timing {
+            zero_high: 70,
+            ..Timing::default()
+        })));
+
+    assert!(matches!(
+        dht.read(&mut NoDelay),
+        Err(DhtError::CrcMismatch(..))
+    ));
+}
+
+#[test]
+fn dropped_bit() {
+    let mut dht = Dht22::from_transport(sim(&Waveform::from_frame(FRAME).drop_bit(10)));
+
+    assert!(dht.read(&mut NoDelay).is_err());
+}
+
+#[test]
+fn round_trips() {
+    let sensor = |frame| sim(&Waveform::from_frame(frame).jitter(5, 42));
+
+    assert_round_trip::<model::Dht22, _>(reading(-12.3, 45.6), sensor);
+    assert_round_trip::<model::Dht22Type2, _>(reading(-10.5, 99.9), sensor);
+    assert_round_trip::<model::Dht11, _>(reading(23, 41), sensor);
+}
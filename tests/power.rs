@@ -0,0 +1,74 @@
+use core::convert::Infallible;
+
+use dht_pio::mock::{MockDelay, MockDht, MockResponse};
+use dht_pio::power::Powered;
+use dht_pio::{Dht22, Transport};
+use embedded_hal::digital::{ErrorType, OutputPin};
+
+const MS: u64 = 1_000_000;
+
+#[derive(Default)]
+struct PowerPin;
+
+impl ErrorType for PowerPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for PowerPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// Transport driving the line high when cancelled, like the PIO ones.
+#[derive(Default)]
+struct Line {
+    high: bool,
+}
+
+impl Transport for Line {
+    fn cancel(&mut self) {
+        self.high = true;
+    }
+
+    fn set_sensor_power(&mut self, powered: bool) {
+        self.high = powered;
+    }
+}
+
+#[test]
+fn power_cycle_waits_before_the_next_power_on() {
+    let script = [
+        MockResponse::Timeout,
+        MockResponse::Data {
+            humidity: 500,
+            temperature: 200,
+        },
+    ];
+    let transport =
+        Powered::new(MockDht::new(script.into_iter()), PowerPin, 2000).power_cycle_after(1);
+    let mut dht = Dht22::from_transport(transport);
+    let mut delay = MockDelay::default();
+
+    assert!(dht.read(&mut delay).is_err());
+    // Warm-up only, the failing read returns as soon as the sensor is off
+    assert_eq!(delay.elapsed_ns(), 2000 * MS);
+
+    assert!(dht.read(&mut delay).is_ok());
+    // Off time of the power cycle, then warm-up
+    assert_eq!(delay.elapsed_ns(), (2000 + 1000 + 2000) * MS);
+}
+
+#[test]
+fn cancel_keeps_the_line_low_while_the_sensor_is_off() {
+    let mut dht = Dht22::from_transport(Powered::new(Line::default(), PowerPin, 2000));
+
+    dht.cancel();
+
+    let (line, _) = dht.into_transport().free();
+    assert!(!line.high);
+}
@@ -0,0 +1,92 @@
+use dht_pio::history::History;
+use dht_pio::trend::{Direction, Limits, Trend};
+use dht_pio::{DhtResult, Duration, Instant};
+
+fn minutes(minutes: u64) -> Instant {
+    Instant::from_ticks(minutes * 60 * 1_000_000)
+}
+
+#[test]
+fn float_rates() {
+    let mut history = History::<16>::new(Duration::minutes(10));
+    // +1°C and -6%RH per hour
+    for i in 0..7 {
+        let reading = DhtResult {
+            temperature: 20.0 + i as f32 / 6.0,
+            humidity: 60.0 - i as f32,
+        };
+        history.push(reading, minutes(i * 10));
+    }
+
+    let rates = Trend::new().analyze(&history).unwrap();
+    assert!((rates.temperature.per_hour - 1.0).abs() < 0.01);
+    assert_eq!(rates.temperature.direction, Direction::Rising);
+    assert!(!rates.temperature.rapid);
+    assert!((rates.humidity.per_hour + 6.0).abs() < 0.01);
+    assert_eq!(rates.humidity.direction, Direction::Falling);
+
+    let rates = Trend::new()
+        .temperature(Limits {
+            stable: 2.0,
+            rapid: 5.0,
+        })
+        .analyze(&history)
+        .unwrap();
+    assert_eq!(rates.temperature.direction, Direction::Stable);
+}
+
+#[test]
+fn fixed_point_rapid_change() {
+    let mut history = History::<16, u16, u16>::new(Duration::minutes(1));
+    for i in 0..10 {
+        history.push(
+            DhtResult {
+                temperature: 21,
+                humidity: 40,
+            },
+            minutes(i),
+        );
+    }
+    // Door opening: humidity jumps over the last minutes
+    history.push(
+        DhtResult {
+            temperature: 21,
+            humidity: 50,
+        },
+        minutes(10),
+    );
+    history.push(
+        DhtResult {
+            temperature: 21,
+            humidity: 60,
+        },
+        minutes(11),
+    );
+
+    let rates = Trend::new().analyze(&history).unwrap();
+    assert_eq!(rates.temperature.per_hour, 0.0);
+    assert_eq!(rates.temperature.direction, Direction::Stable);
+    assert!(rates.humidity.rapid);
+
+    // 10%RH per minute over the window
+    let rates = Trend::new()
+        .window(Duration::minutes(2))
+        .analyze(&history)
+        .unwrap();
+    assert!((rates.humidity.per_hour - 600.0).abs() < 0.1);
+}
+
+#[test]
+fn not_enough_samples() {
+    let mut history = History::<4>::new(Duration::minutes(1));
+    assert!(Trend::new().analyze(&history).is_none());
+
+    history.push(
+        DhtResult {
+            temperature: 20.0,
+            humidity: 50.0,
+        },
+        minutes(0),
+    );
+    assert!(Trend::new().analyze(&history).is_none());
+}